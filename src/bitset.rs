@@ -0,0 +1,123 @@
+use std::ops::BitAndAssign;
+
+/// A set of indices below a fixed length, e.g. of the words in a list, stored
+/// as one bit per index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitSet {
+    len: usize,
+    blocks: Vec<u64>,
+}
+
+impl BitSet {
+    /// The set of all indices below `len`.
+    pub fn full(len: usize) -> Self {
+        let mut blocks = vec![u64::MAX; len.div_ceil(64)];
+        if let Some(last) = blocks.last_mut() {
+            *last >>= len.div_ceil(64) * 64 - len;
+        }
+        Self { len, blocks }
+    }
+
+    /// The set of the indices below `len` for which `f` returns true. The
+    /// blocks are built without branching on `f`.
+    pub fn from_fn(len: usize, mut f: impl FnMut(usize) -> bool) -> Self {
+        let blocks = (0..len)
+            .step_by(64)
+            .map(|start| {
+                (start..len.min(start + 64))
+                    .fold(0, |block, i| block | u64::from(f(i)) << (i - start))
+            })
+            .collect();
+        Self { len, blocks }
+    }
+
+    pub fn count(&self) -> usize {
+        self.blocks
+            .iter()
+            .map(|block| block.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.iter().all(|&block| block == 0)
+    }
+
+    /// Only keeps the indices for which `f` returns true.
+    pub fn retain(&mut self, mut f: impl FnMut(usize) -> bool) {
+        for (i, block) in self.blocks.iter_mut().enumerate() {
+            let mut bits = *block;
+            while bits != 0 {
+                let bit = bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                if !f(i * 64 + bit) {
+                    *block &= !(1 << bit);
+                }
+            }
+        }
+    }
+
+    /// The indices in the set, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.blocks.iter().enumerate().flat_map(|(i, &block)| {
+            let mut bits = block;
+            std::iter::from_fn(move || {
+                (bits != 0).then(|| {
+                    let bit = bits.trailing_zeros() as usize;
+                    bits &= bits - 1;
+                    i * 64 + bit
+                })
+            })
+        })
+    }
+
+    /// The items of `list` at the indices in the set.
+    pub fn select<'a, T>(&'a self, list: &'a [T]) -> impl Iterator<Item = &'a T> + 'a {
+        assert_eq!(
+            self.len,
+            list.len(),
+            "list should have the length of the set"
+        );
+        self.iter().map(|index| &list[index])
+    }
+}
+
+/// Intersects the sets a block at a time.
+impl BitAndAssign<&BitSet> for BitSet {
+    fn bitand_assign(&mut self, other: &BitSet) {
+        assert_eq!(self.len, other.len, "sets should have the same length");
+        for (block, other) in self.blocks.iter_mut().zip(&other.blocks) {
+            *block &= other;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    #[test]
+    fn test_bit_set() {
+        for len in [0, 1, 63, 64, 65, 200] {
+            let full = BitSet::full(len);
+            assert_eq!(full.count(), len);
+            assert!(full.iter().eq(0..len));
+
+            let mut set = full.clone();
+            set.retain(|i| i % 3 == 0);
+            assert_eq!(set.iter().collect_vec(), (0..len).step_by(3).collect_vec());
+            assert_eq!(set.count(), len.div_ceil(3));
+            set.retain(|i| i % 2 == 0);
+            assert_eq!(set.iter().collect_vec(), (0..len).step_by(6).collect_vec());
+            set.retain(|_| false);
+            assert!(set.is_empty());
+
+            let mut set = BitSet::from_fn(len, |i| i % 3 == 0);
+            assert_eq!(set.iter().collect_vec(), (0..len).step_by(3).collect_vec());
+            set &= &BitSet::from_fn(len, |i| i % 2 == 0);
+            assert_eq!(set.iter().collect_vec(), (0..len).step_by(6).collect_vec());
+            set &= &full;
+            assert_eq!(set.count(), len.div_ceil(6));
+        }
+    }
+}
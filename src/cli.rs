@@ -0,0 +1,299 @@
+use crate::{
+    game::{Game, Mode, Round},
+    report::Format,
+    strategy::StrategyKind,
+    table,
+    words::{self, Word, WordList},
+};
+use clap::{builder::PossibleValue, Args, Parser, Subcommand, ValueEnum};
+use color_eyre::{eyre::bail, Result};
+use rand::{seq::index, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::{collections::HashSet, path::PathBuf};
+
+/// Suggests guesses for Wordle, interactively or from the command line.
+#[derive(Debug, Parser)]
+#[command(version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[command(flatten)]
+    pub options: Options,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Play interactively in the terminal (the default).
+    #[command(visible_alias = "tui")]
+    Play,
+    /// Let the solver find a secret word and show its guesses.
+    Simulate {
+        /// The secret word.
+        word: String,
+    },
+    /// Let the solver find every answer and report how many guesses it needs.
+    Bench {
+        #[command(flatten)]
+        subset: Subset,
+    },
+    /// Let several strategies find every answer, and compare how many guesses
+    /// they need.
+    Compare {
+        /// How many of the words where the strategies differ most to print.
+        #[arg(short = 'n', long, default_value_t = 20)]
+        count: usize,
+        #[command(flatten)]
+        subset: Subset,
+    },
+    /// Print the number of words that could be the secret and the best
+    /// guesses.
+    Suggest {
+        /// How many guesses to print.
+        #[arg(short = 'n', long, default_value_t = 10)]
+        count: usize,
+        /// The guesses so far with their feedback: b for black, y for yellow
+        /// and g for green, e.g. crane:bybbg.
+        #[arg(value_name = "GUESS:FEEDBACK")]
+        rounds: Vec<Round>,
+    },
+    /// Read guesses with their feedback from stdin, one per line, and write
+    /// the next suggestion for each. See the README for the protocol.
+    Pipe,
+    /// Print the words that could be the secret.
+    Filter {
+        /// The guesses so far with their feedback, like for `suggest`.
+        #[arg(value_name = "GUESS:FEEDBACK")]
+        rounds: Vec<Round>,
+    },
+}
+
+/// Options shared by all commands.
+#[derive(Debug, Args)]
+pub struct Options {
+    /// The heuristic to choose guesses with. `bench` and `compare` accept
+    /// more than one.
+    #[arg(long = "strategy", global = true, value_name = "NAME")]
+    pub strategies: Vec<StrategyKind>,
+    /// Allow any word as a guess, instead of only words that are consistent
+    /// with the feedback so far (hard mode).
+    #[arg(long, global = true)]
+    pub normal: bool,
+    /// The list of allowed guesses, instead of the embedded list.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub words: Option<PathBuf>,
+    /// The list of words that can be the answer, instead of the embedded list
+    /// (or, with `--words`, all allowed guesses).
+    #[arg(long, global = true, value_name = "PATH")]
+    pub answers: Option<PathBuf>,
+    /// Store the table of feedback patterns in this directory, to reuse it on
+    /// later runs.
+    #[arg(long, global = true, value_name = "DIR")]
+    pub cache: Option<PathBuf>,
+    /// How `simulate`, `bench` and `compare` print their results.
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub format: Format,
+    /// The number of guesses the game allows.
+    #[arg(long, global = true, default_value_t = 6)]
+    pub max_guesses: usize,
+}
+
+/// Options to benchmark a part of the answers, which is quicker.
+#[derive(Debug, Args)]
+pub struct Subset {
+    /// Only the answers listed in this file (in the format of a word list).
+    #[arg(long, value_name = "PATH")]
+    pub only: Option<PathBuf>,
+    /// Only the answers that match this template: five letters or dots for
+    /// any letter, e.g. ".r.ne".
+    #[arg(long, value_name = "TEMPLATE")]
+    pub matching: Option<String>,
+    /// A random sample of this many answers.
+    #[arg(long, value_name = "SIZE")]
+    pub sample: Option<usize>,
+    /// The seed for the random sample, to get the same sample every time.
+    #[arg(long, default_value_t = 0, requires = "sample")]
+    pub seed: u64,
+}
+
+impl Subset {
+    /// The answers to benchmark, in the order of [`Word::answers`].
+    pub fn answers(&self) -> Result<Vec<Word>> {
+        let mut answers = Word::answers().to_vec();
+        if let Some(path) = &self.only {
+            let known: HashSet<Word> = answers.iter().copied().collect();
+            let only: HashSet<Word> = WordList::from_file(path)?.words().iter().copied().collect();
+            if let Some(word) = only.iter().find(|word| !known.contains(word)) {
+                bail!("{word} in {} is not one of the answers", path.display());
+            }
+            answers.retain(|word| only.contains(word));
+        }
+        if let Some(template) = &self.matching {
+            let template = template.to_ascii_lowercase();
+            if template.len() != 5
+                || !template
+                    .chars()
+                    .all(|ch| ch == '.' || ch.is_ascii_lowercase())
+            {
+                bail!("invalid template \"{template}\", expected five letters or dots");
+            }
+            answers.retain(|word| {
+                word.to_string()
+                    .chars()
+                    .zip(template.chars())
+                    .all(|(letter, ch)| ch == '.' || ch == letter)
+            });
+        }
+        if let Some(size) = self.sample.filter(|&size| size < answers.len()) {
+            // Unlike `StdRng`, this gives the same sample on every platform and
+            // version of `rand`.
+            let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+            let mut indices = index::sample(&mut rng, answers.len(), size).into_vec();
+            indices.sort_unstable();
+            answers = indices.into_iter().map(|i| answers[i]).collect();
+        }
+        if answers.is_empty() {
+            bail!("no answers to benchmark");
+        }
+        Ok(answers)
+    }
+}
+
+impl Options {
+    /// Loads the word lists and sets up the cache, before any game starts.
+    pub fn install(&self) -> Result<()> {
+        if let Some(dir) = &self.cache {
+            table::set_cache_dir(dir.clone())?;
+        }
+        if self.words.is_some() || self.answers.is_some() {
+            let guesses = match &self.words {
+                Some(path) => WordList::from_file(path)?,
+                None => WordList::embedded(),
+            };
+            let answers = match (&self.answers, &self.words) {
+                (Some(path), _) => Some(WordList::from_file(path)?),
+                // The embedded answers might not fit another list of guesses.
+                (None, Some(_)) => None,
+                (None, None) => Some(WordList::embedded_answers()),
+            };
+            words::install(guesses, answers)?;
+        }
+        Ok(())
+    }
+
+    pub fn mode(&self) -> Mode {
+        if self.normal {
+            Mode::Normal
+        } else {
+            Mode::Hard
+        }
+    }
+
+    /// A new game in which `kind` makes the guesses.
+    pub fn game(&self, kind: StrategyKind) -> Game {
+        Game::new(kind.build(self.max_guesses), self.mode())
+    }
+
+    /// The first strategy given, for commands that use only one.
+    pub fn strategy(&self) -> StrategyKind {
+        self.strategies.first().copied().unwrap_or_default()
+    }
+
+    /// All strategies given, or the default strategy.
+    pub fn strategies(&self) -> Vec<StrategyKind> {
+        match self.strategies[..] {
+            [] => vec![StrategyKind::default()],
+            _ => self.strategies.clone(),
+        }
+    }
+}
+
+impl ValueEnum for StrategyKind {
+    fn value_variants<'a>() -> &'a [Self] {
+        &Self::ALL
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn subset() -> Subset {
+        Subset {
+            only: None,
+            matching: None,
+            sample: None,
+            seed: 0,
+        }
+    }
+
+    #[test]
+    fn test_only() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("wrdl-only-{}.txt", std::process::id()));
+        fs::write(&path, "speed\nabbey\n")?;
+        let answers = Subset {
+            only: Some(path.clone()),
+            ..subset()
+        }
+        .answers();
+        fs::write(&path, "speed\nzzzzz\n")?;
+        let unknown = Subset {
+            only: Some(path.clone()),
+            ..subset()
+        }
+        .answers();
+        fs::remove_file(&path)?;
+        let words = ["abbey", "speed"].map(|word| Word::try_from(word).unwrap());
+        assert_eq!(answers?, words);
+        assert!(unknown.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_matching() -> Result<()> {
+        let answers = Subset {
+            matching: Some(".R.NE".to_string()),
+            ..subset()
+        }
+        .answers()?;
+        assert!(!answers.is_empty());
+        assert!(answers.contains(&Word::try_from("crane")?));
+        for word in answers {
+            let word = word.to_string();
+            assert_eq!((&word[1..2], &word[3..]), ("r", "ne"));
+        }
+        let invalid = Subset {
+            matching: Some("cr.n".to_string()),
+            ..subset()
+        };
+        assert!(invalid.answers().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sample() -> Result<()> {
+        let sample = |seed| {
+            Subset {
+                sample: Some(100),
+                seed,
+                ..subset()
+            }
+            .answers()
+        };
+        let answers = sample(0)?;
+        assert_eq!(answers.len(), 100);
+        assert!(answers.iter().all(|word| Word::answers().contains(word)));
+        let ids = answers
+            .iter()
+            .map(|&word| Word::answers().iter().position(|&answer| answer == word))
+            .collect::<Vec<_>>();
+        assert!(ids.is_sorted());
+        assert_eq!(sample(0)?, answers);
+        assert_ne!(sample(1)?, answers);
+        Ok(())
+    }
+}
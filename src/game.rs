@@ -1,52 +1,191 @@
 use crate::{
-    stats::LetterStats,
-    words::{Feedback, Filter, Word},
+    bitset::BitSet,
+    strategy::{LetterFrequency, Strategy},
+    table::PatternTable,
+    words::{Filter, Pattern, Word},
 };
+use color_eyre::{eyre::eyre, Report, Result};
 use itertools::Itertools;
+use std::{str::FromStr, sync::Arc};
+
+/// Which words may be guessed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Every guess has to be consistent with all feedback so far.
+    #[default]
+    Hard,
+    /// Any word from the list may be guessed, e.g. to probe for letters.
+    Normal,
+}
+
+/// A guess and the feedback it got.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Round {
+    pub guess: Word,
+    pub pattern: Pattern,
+}
+
+impl FromStr for Round {
+    type Err = Report;
+
+    /// Parses the guess and the feedback separated by a colon, e.g.
+    /// "crane:bybbg".
+    fn from_str(s: &str) -> Result<Self> {
+        let parse = || {
+            let (guess, pattern) = s
+                .split_once(':')
+                .ok_or_else(|| eyre!("expected <guess>:<feedback>"))?;
+            Ok::<_, Report>(Self {
+                guess: guess.try_into()?,
+                pattern: pattern.parse()?,
+            })
+        };
+        parse().map_err(|error| eyre!("invalid round \"{s}\": {error}"))
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct Game {
-    list: Vec<Word>,
+    /// The answers that are consistent with all feedback so far, by their
+    /// position in [`Word::answers`].
+    answers: BitSet,
+    /// The allowed guesses that are consistent with all feedback so far, by
+    /// their position in [`Word::list`].
+    guesses: BitSet,
     filter: Filter,
-    stats: LetterStats,
+    /// The number of guesses so far.
+    rounds: usize,
+    strategy: Arc<dyn Strategy>,
+    mode: Mode,
 }
 
 impl Game {
+    pub fn new(strategy: Arc<dyn Strategy>, mode: Mode) -> Self {
+        Self {
+            answers: BitSet::full(Word::answers().len()),
+            guesses: BitSet::full(Word::list().len()),
+            filter: Default::default(),
+            rounds: 0,
+            strategy,
+            mode,
+        }
+    }
+
     pub fn suggested_word(&self) -> Option<Word> {
         self.suggested_words(1).next()
     }
 
+    /// Suggestions out of all words that may be guessed in the current mode.
     pub fn suggested_words(&self, n: usize) -> impl Iterator<Item = Word> + '_ {
-        self.list
-            .iter()
-            .copied()
-            .k_largest_by_key(n, |&w| self.stats.relevance(w))
+        let candidates = self.words().collect_vec();
+        // There is nothing left to probe for without candidates.
+        let guesses = match self.mode {
+            Mode::Normal if !self.answers.is_empty() => Word::list().to_vec(),
+            Mode::Normal => candidates.clone(),
+            Mode::Hard => self.guesses.select(Word::list()).copied().collect(),
+        };
+        self.strategy
+            .rank(
+                &self.filter,
+                self.rounds,
+                &candidates,
+                &guesses,
+                Word::weights(),
+                n,
+            )
+            .into_iter()
     }
 
-    pub fn apply_feedback(&mut self, word: Word, feedback: [Feedback; 5]) {
-        self.filter.restrict(word, feedback);
-        self.list.retain(|&w| {
-            let retain = w.matches(&self.filter);
-            if !retain {
-                self.stats.remove_word(w)
-            }
-            retain
-        });
+    /// Suggestions out of the words that could still be the secret.
+    pub fn suggested_candidates(&self, n: usize) -> impl Iterator<Item = Word> + '_ {
+        let candidates = self.words().collect_vec();
+        self.strategy
+            .rank(
+                &self.filter,
+                self.rounds,
+                &candidates,
+                &candidates,
+                Word::weights(),
+                n,
+            )
+            .into_iter()
     }
 
-    pub fn words(&self) -> &[Word] {
-        &self.list
+    pub fn apply_feedback(&mut self, word: Word, pattern: Pattern) {
+        self.filter.restrict(word, pattern);
+        self.rounds += 1;
+        // Looking up the feedback is cheaper than matching against the filter,
+        // but words outside the word list have no row in the table.
+        let (answers, filter) = (Word::answers(), &self.filter);
+        match PatternTable::global().answers_matching(word, pattern) {
+            Some(matching) => self.answers &= &matching,
+            None => self.answers.retain(|i| answers[i].matches(filter)),
+        }
+        let guesses = Word::list();
+        self.guesses.retain(|i| guesses[i].matches(filter));
+    }
+
+    /// Replays the feedback of earlier guesses.
+    pub fn apply_rounds(&mut self, rounds: &[Round]) {
+        for &Round { guess, pattern } in rounds {
+            self.apply_feedback(guess, pattern);
+        }
+    }
+
+    pub fn set_strategy(&mut self, strategy: Arc<dyn Strategy>) {
+        self.strategy = strategy;
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// The words that could still be the secret.
+    pub fn words(&self) -> impl Iterator<Item = Word> + '_ {
+        self.answers.select(Word::answers()).copied()
+    }
+
+    /// The number of words that could still be the secret.
+    pub fn word_count(&self) -> usize {
+        self.answers.count()
     }
 }
 
 impl Default for Game {
     fn default() -> Self {
-        let list = Word::list().to_vec();
-        let stats = list.iter().copied().collect();
-        Self {
-            list,
-            stats,
-            filter: Default::default(),
-        }
+        Self::new(Arc::new(LetterFrequency), Mode::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round() {
+        let round = "crane:bYgbb".parse::<Round>().unwrap();
+        assert_eq!(round.guess, Word::try_from("crane").unwrap());
+        assert_eq!(round.pattern, "bygbb".parse().unwrap());
+        assert!("crane".parse::<Round>().is_err());
+        assert!("cran:bygbb".parse::<Round>().is_err());
+        assert!("crane:bygb".parse::<Round>().is_err());
+    }
+
+    #[test]
+    fn test_opening_suggestions() {
+        let suggestions = Game::default().suggested_words(10).collect_vec();
+        assert_eq!(
+            suggestions.iter().join(" "),
+            "oater orate roate realo irate retia terai tiare alert alter"
+        );
+
+        // The order of the word lists makes no difference.
+        let answers = Word::answers().iter().rev().copied().collect_vec();
+        let guesses = Word::list().iter().rev().copied().collect_vec();
+        let filter = Filter::default();
+        assert_eq!(
+            LetterFrequency.rank(&filter, 0, &answers, &guesses, Word::weights(), 10),
+            suggestions
+        );
     }
 }
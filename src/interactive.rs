@@ -1,7 +1,8 @@
 use crate::{
-    game::Game,
+    game::{Game, Mode},
+    strategy::StrategyKind,
     tui::{Event, Tui},
-    words::{Feedback, Letter, LetterSet},
+    words::{Feedback, Letter, LetterSet, Word},
 };
 use color_eyre::eyre::Result;
 use crossterm::event::KeyCode;
@@ -18,7 +19,17 @@ pub struct App {
     rows: Vec<Row>,
     cursor: usize,
     feedback_mode: bool,
+    strategy: StrategyKind,
+    /// The number of guesses the game allows, which strategies may plan for.
+    max_guesses: usize,
     game: Game,
+    /// Cached, as ranking can be expensive with some strategies.
+    suggestions: Vec<Word>,
+    /// The suggested guess, which fills a new row.
+    guess: Option<Word>,
+    /// The suggested guess if it cannot be the secret, shown above the list.
+    /// In normal mode, the best probe is always shown.
+    probe: Option<Word>,
 }
 
 enum Action {
@@ -27,14 +38,22 @@ enum Action {
 }
 
 impl App {
-    pub fn new() -> Self {
+    const SUGGESTIONS: usize = 18;
+
+    pub fn new(strategy: StrategyKind, mode: Mode, max_guesses: usize) -> Self {
         let mut app = App {
             rows: vec![Row::default()],
             cursor: 0,
             feedback_mode: false,
-            game: Default::default(),
+            strategy,
+            max_guesses,
+            game: Game::new(strategy.build(max_guesses), mode),
+            suggestions: Vec::new(),
+            guess: None,
+            probe: None,
         };
         app.active_block_mut().selected = true;
+        app.update_suggestions();
         app.fill_suggested_word();
         app
     }
@@ -75,7 +94,7 @@ impl App {
         // Render rows to the left
         let row_areas = Layout::vertical(
             iter::once(Constraint::Fill(1))
-                .chain(iter::repeat(Constraint::Length(3)).take(self.rows.len()))
+                .chain(iter::repeat_n(Constraint::Length(3), self.rows.len()))
                 .chain(iter::once(Constraint::Length(1)))
                 .chain(iter::once(Constraint::Fill(1))),
         )
@@ -94,17 +113,46 @@ impl App {
             "ENTER WORD"
         })
         .render(mode_area, buf);
+        Paragraph::new(Line::from(vec![
+            "strategy: ".dark_gray(),
+            self.strategy.to_span(),
+            " (tab)".dark_gray(),
+        ]))
+        .alignment(Alignment::Right)
+        .render(mode_area, buf);
 
-        // Render word list to the right
-        let [_, word_area, _] = Layout::vertical([
+        // Render the best probe and the list of candidates to the right
+        let probe_height = match self.game.mode() {
+            Mode::Hard if self.probe.is_none() => 0,
+            _ => 3,
+        };
+        let [_, probe_area, word_area, _] = Layout::vertical([
             Constraint::Fill(1),
-            Constraint::Max(20),
+            Constraint::Length(probe_height),
+            Constraint::Max(Self::SUGGESTIONS as u16 + 2),
             Constraint::Fill(1),
         ])
         .areas(right);
+        if let Some(probe) = self.probe {
+            Paragraph::new(probe.to_string())
+                .block(
+                    const {
+                        Block::bordered()
+                            .border_type(BorderType::Plain)
+                            .padding(Padding::horizontal(1))
+                    }
+                    .title(Line::from(vec![
+                        "╢".into(),
+                        "probe".dark_gray(),
+                        "╟".into(),
+                    ])),
+                )
+                .render(probe_area, buf);
+        }
         Paragraph::new(
-            self.game
-                .suggested_words(word_area.height as usize - 2)
+            self.suggestions
+                .iter()
+                .take(word_area.height.saturating_sub(2) as usize)
                 .format("\n")
                 .to_string(),
         )
@@ -116,7 +164,7 @@ impl App {
             }
             .title(Line::from(vec![
                 "╢".into(),
-                self.game.words().len().to_string().dark_gray(),
+                self.game.word_count().to_string().dark_gray(),
                 "╟".into(),
             ])),
         )
@@ -157,6 +205,16 @@ impl App {
                 self.apply_expected_feedback();
                 Some(Action::Draw)
             }
+            KeyCode::Tab => {
+                self.strategy = self.strategy.next();
+                self.game
+                    .set_strategy(self.strategy.build(self.max_guesses));
+                self.update_suggestions();
+                if self.cursor == 0 {
+                    self.fill_suggested_word();
+                }
+                Some(Action::Draw)
+            }
             KeyCode::Esc => Some(Action::Exit),
             _ => None,
         }
@@ -167,16 +225,13 @@ impl App {
             KeyCode::Enter => {
                 self.active_block_mut().selected = false;
                 let row = self.last_row();
-                let word = row
-                    .letters
-                    .each_ref()
-                    .map(|l| l.contents.expect("all letters should be set by now"))
-                    .into();
-                let feedback = row.letters.each_ref().map(|l| l.color);
-                self.game.apply_feedback(word, feedback);
+                let word = row.word().expect("all letters should be set by now");
+                let pattern = row.letters.each_ref().map(|l| l.color).into();
+                self.game.apply_feedback(word, pattern);
                 self.rows.push(Row::default());
                 self.set_cursor(0);
                 self.feedback_mode = false;
+                self.update_suggestions();
                 self.fill_suggested_word();
                 Some(Action::Draw)
             }
@@ -230,8 +285,21 @@ impl App {
         &mut self.last_row_mut().letters[cursor]
     }
 
+    fn update_suggestions(&mut self) {
+        self.suggestions = self.game.suggested_candidates(Self::SUGGESTIONS).collect();
+        self.guess = self.game.suggested_word();
+        self.probe = match self.game.mode() {
+            Mode::Hard => self
+                .guess
+                .filter(|&guess| !self.game.words().contains(&guess)),
+            Mode::Normal => self.guess,
+        };
+    }
+
+    /// Fills the last row with the suggested guess, the same word that
+    /// `suggest` would print first.
     fn fill_suggested_word(&mut self) {
-        let Some(word) = self.game.suggested_word() else {
+        let Some(word) = self.guess else {
             return;
         };
         self.last_row_mut()
@@ -246,27 +314,54 @@ impl App {
     }
 
     fn apply_expected_feedback(&mut self) {
+        // In normal mode the word may be a probe, which has to stay as typed.
+        if self.game.mode() == Mode::Hard {
+            self.fill_shared_letters();
+        }
+
+        // Feedback that all candidates agree on is marked.
+        let guess = self
+            .last_row()
+            .word()
+            .expect("all letters should be set by now");
+        let mut patterns = self
+            .game
+            .words()
+            .map(|secret| Word::score(guess, secret).feedback());
+        let Some(first) = patterns.next() else {
+            return;
+        };
+        let mut expected = first.map(Some);
+        for pattern in patterns {
+            for (expected, feedback) in expected.iter_mut().zip(pattern) {
+                if *expected != Some(feedback) {
+                    *expected = None;
+                }
+            }
+        }
+        for (block, feedback) in self.last_row_mut().letters.iter_mut().zip(expected) {
+            if let Some(feedback) = feedback {
+                block.color = feedback;
+            }
+        }
+    }
+
+    /// Fills in the letters that all candidates share.
+    fn fill_shared_letters(&mut self) {
         let mut remaining_letters = [LetterSet::EMPTY; 5];
-        let mut known_mandatory_letters = LetterSet::FULL;
-        for &word in self.game.words() {
-            known_mandatory_letters = known_mandatory_letters.intersect(word.into());
+        for word in self.game.words() {
             for (set, letter) in remaining_letters.iter_mut().zip(word.iter()) {
                 set.insert(letter);
             }
         }
-        let last_row = self.last_row_mut();
-        for (block, letter) in last_row.letters.iter_mut().zip(remaining_letters) {
+        for (block, letter) in self
+            .last_row_mut()
+            .letters
+            .iter_mut()
+            .zip(remaining_letters)
+        {
             if let Ok(letter) = letter.into_iter().exactly_one() {
                 block.contents = Some(letter);
-                block.color = Feedback::Green;
-                known_mandatory_letters.remove(letter);
-            }
-        }
-        for maybe_misplaced in known_mandatory_letters {
-            for block in last_row.letters.iter_mut() {
-                if block.contents == Some(maybe_misplaced) && block.color == Feedback::Black {
-                    block.color = Feedback::Yellow;
-                }
             }
         }
     }
@@ -278,6 +373,11 @@ struct Row {
 }
 
 impl Row {
+    fn word(&self) -> Option<Word> {
+        let [a, b, c, d, e] = self.letters.each_ref().map(|l| l.contents);
+        Some([a?, b?, c?, d?, e?].into())
+    }
+
     fn render(&self, area: Rect, buf: &mut Buffer) {
         let areas = Layout::horizontal([Constraint::Length(7); 5])
             .spacing(1)
@@ -311,7 +411,7 @@ impl LetterBlock {
         paragraph.block(self.block()).render(area, buf);
     }
 
-    fn block(&self) -> Block {
+    fn block(&self) -> Block<'_> {
         if self.selected {
             const {
                 Block::bordered()
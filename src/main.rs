@@ -1,49 +1,112 @@
-use color_eyre::eyre::Result;
-use indicatif::ParallelProgressIterator;
-use itertools::Itertools;
+use clap::Parser;
+use cli::{Cli, Command, Options};
+use color_eyre::eyre::{bail, Result};
+use indicatif::{ParallelProgressIterator, ProgressBar, ProgressStyle};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-use simulation::Simulation;
-use std::env::args;
+use report::{BenchReport, Comparison, GameReport};
+use std::io;
+use strategy::StrategyKind;
 use words::Word;
 
+mod bitset;
+mod cli;
 mod errors;
 mod game;
 mod interactive;
+mod pipe;
+mod report;
 mod simulation;
+mod solver;
 mod stats;
+mod strategy;
+mod table;
 mod tui;
 mod words;
 
 fn main() -> Result<()> {
     errors::install_hooks()?;
-    match args().nth(1).as_deref() {
-        Some("all") => {
-            let results = Word::list()
-                .par_iter()
-                .map(|&word| (word, Simulation::new(word).run().count()))
-                .progress()
-                .collect::<Vec<_>>();
-            let (min, max) = results.iter().minmax_by_key(|t| t.1).into_option().unwrap();
-            println!("Min: {} in {} rounds", min.0, min.1);
-            println!("Max: {} in {} rounds", max.0, max.1);
-            println!(
-                "Avg: {:.2}",
-                results.iter().map(|t| t.1 as f64).sum::<f64>() / results.len() as f64
-            );
-            let failed = results.iter().filter(|t| t.1 > 6).count();
-            let perc = failed as f64 / Word::list().len() as f64 * 100.0;
-            println!("Failed words: {} ({perc:.2}%)", failed);
+    let Cli { command, options } = Cli::parse();
+    options.install()?;
+    match command.unwrap_or(Command::Play) {
+        Command::Play => {
+            interactive::App::new(options.strategy(), options.mode(), options.max_guesses).run()
+        }
+        Command::Simulate { word } => simulate(&options, Word::try_from(word.as_str())?),
+        Command::Bench { subset } => {
+            let reports = bench(&options, &subset.answers()?)?;
+            BenchReport::write_all(&reports, options.format, io::stdout().lock())
+        }
+        Command::Compare { count, subset } => {
+            if options.strategies().len() < 2 {
+                bail!("compare needs at least two strategies, e.g. --strategy frequency --strategy entropy");
+            }
+            let comparison = Comparison::new(bench(&options, &subset.answers()?)?, count);
+            comparison.write(options.format, io::stdout().lock())
+        }
+        Command::Suggest { count, rounds } => {
+            let mut game = options.game(options.strategy());
+            game.apply_rounds(&rounds);
+            println!("Candidates: {}", game.word_count());
+            for word in game.suggested_words(count) {
+                println!("{word}");
+            }
             Ok(())
         }
-        Some(word) => {
-            let word = Word::try_from(word)?;
-            println!("Starting simulation with word \"{word}\"");
-            for round in Simulation::new(word).run() {
-                let (guess, feedback) = round?;
-                println!("Guess: {guess}, feedback: {feedback:?}");
+        Command::Pipe => pipe::run(
+            options.game(options.strategy()),
+            io::stdin().lock(),
+            io::stdout().lock(),
+        ),
+        Command::Filter { rounds } => {
+            let mut game = options.game(options.strategy());
+            game.apply_rounds(&rounds);
+            for word in game.words() {
+                println!("{word}");
             }
             Ok(())
         }
-        None => interactive::App::new().run(),
     }
 }
+
+fn simulate(options: &Options, word: Word) -> Result<()> {
+    if !Word::answers().contains(&word) {
+        bail!("\"{word}\" is not one of the answers, see --answers");
+    }
+    let strategy = options.strategy();
+    let game = options.game(strategy);
+    GameReport::play(word, game, options.max_guesses)?.write(
+        strategy,
+        options.format,
+        io::stdout().lock(),
+    )
+}
+
+fn bench(options: &Options, answers: &[Word]) -> Result<Vec<BenchReport>> {
+    options
+        .strategies()
+        .into_iter()
+        .map(|kind| {
+            let game = options.game(kind);
+            let games = answers
+                .par_iter()
+                .map(|&word| GameReport::play(word, game.clone(), options.max_guesses))
+                .progress_with(progress_bar(kind, answers.len()))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(BenchReport::new(
+                kind,
+                games,
+                options.max_guesses,
+                Word::answers().len(),
+                Word::weights(),
+            ))
+        })
+        .collect()
+}
+
+/// A progress bar for the games of a strategy.
+fn progress_bar(kind: StrategyKind, games: usize) -> ProgressBar {
+    let style = ProgressStyle::with_template("{msg} {wide_bar} {pos}/{len}").unwrap();
+    ProgressBar::new(games as u64)
+        .with_style(style)
+        .with_message(kind.to_string())
+}
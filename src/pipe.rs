@@ -0,0 +1,74 @@
+use crate::game::{Game, Round};
+use color_eyre::Result;
+use std::io::{BufRead, Write};
+
+/// Plays `game` driven by lines of `input`, e.g. from another program.
+///
+/// Every line holds a guess with its feedback (like `crane:bybbg`), `undo` to
+/// take back the last guess, or `reset` to start over. Every line gets a line
+/// of output with the suggested guess and the number of words that could be
+/// the secret, separated by a space (`-` when there is no suggestion), or a
+/// line starting with `error:` if the line was invalid.
+pub fn run(game: Game, input: impl BufRead, mut output: impl Write) -> Result<()> {
+    // The game after every guess, so guesses can be taken back.
+    let mut history = vec![game];
+    for line in input.lines() {
+        let line = line?;
+        let result = match line.trim() {
+            "" => continue,
+            "reset" => {
+                history.truncate(1);
+                Ok(())
+            }
+            "undo" => {
+                if history.len() > 1 {
+                    history.pop();
+                }
+                Ok(())
+            }
+            round => round.parse().map(|Round { guess, pattern }| {
+                let mut game = history.last().unwrap().clone();
+                game.apply_feedback(guess, pattern);
+                history.push(game);
+            }),
+        };
+        let game = history.last().unwrap();
+        match result.map(|()| game.suggested_word()) {
+            Ok(Some(word)) => writeln!(output, "{word} {}", game.word_count())?,
+            Ok(None) => writeln!(output, "- {}", game.word_count())?,
+            Err(error) => writeln!(output, "error: {error}")?,
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::words::Word;
+
+    #[test]
+    fn test_run() {
+        let input = "crane:bybbg\n\nundo\nundo\ncrane:bybbg\nreset\ncrane:bybb\n";
+        let mut output = Vec::new();
+        run(Game::default(), input.as_bytes(), &mut output).unwrap();
+        let opening = format!("oater {}", Word::answers().len());
+        let after_crane = "rouse 40";
+        let expected = [
+            after_crane,
+            &opening,
+            &opening,
+            after_crane,
+            &opening,
+            "error: invalid round \"crane:bybb\": feedback must have length 5",
+        ];
+        assert_eq!(
+            String::from_utf8(output)
+                .unwrap()
+                .lines()
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+}
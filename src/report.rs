@@ -0,0 +1,575 @@
+use crate::{
+    game::Game,
+    simulation::Simulation,
+    strategy::StrategyKind,
+    words::{Pattern, Weights, Word},
+};
+use clap::ValueEnum;
+use color_eyre::Result;
+use itertools::Itertools;
+use serde::Serialize;
+use std::{cmp::Reverse, io::Write};
+
+/// How `simulate`, `bench` and `compare` print their results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Human readable text.
+    #[default]
+    Text,
+    /// A JSON document.
+    Json,
+    /// A CSV table with a row per game. For `bench`, it comes after a table
+    /// with the statistics of each strategy, separated by an empty line.
+    Csv,
+}
+
+const CSV_HEADER: &str = "strategy,secret,rounds,failed,guesses,patterns";
+const CSV_SUMMARY_HEADER: &str =
+    "strategy,games,min,max,avg,avg_margin,std_dev,p50,p90,p95,p99,failed";
+
+/// The guesses the solver needed to find a secret.
+#[derive(Debug, Clone, Serialize)]
+pub struct GameReport {
+    pub secret: Word,
+    pub guesses: Vec<Word>,
+    pub patterns: Vec<Pattern>,
+    pub rounds: usize,
+    /// Whether the solver needed more guesses than the game allows.
+    pub failed: bool,
+}
+
+impl GameReport {
+    /// Plays `game` until `secret` is found.
+    pub fn play(secret: Word, game: Game, max_guesses: usize) -> Result<Self> {
+        let (guesses, patterns): (Vec<_>, Vec<_>) = Simulation::new(secret, game)
+            .run()
+            .try_collect::<_, Vec<_>, _>()?
+            .into_iter()
+            .unzip();
+        Ok(Self {
+            secret,
+            rounds: guesses.len(),
+            failed: guesses.len() > max_guesses,
+            guesses,
+            patterns,
+        })
+    }
+
+    pub fn write(&self, strategy: StrategyKind, format: Format, mut out: impl Write) -> Result<()> {
+        match format {
+            Format::Text => {
+                writeln!(out, "Starting simulation with word \"{}\"", self.secret)?;
+                for (guess, pattern) in self.guesses.iter().zip(&self.patterns) {
+                    writeln!(out, "Guess: {guess}, feedback: {pattern}")?;
+                }
+                if self.failed {
+                    writeln!(out, "Failed: needed {} guesses", self.rounds)?;
+                }
+            }
+            Format::Json => writeln!(out, "{}", serde_json::to_string_pretty(self)?)?,
+            Format::Csv => {
+                writeln!(out, "{CSV_HEADER}")?;
+                self.write_csv_row(strategy, &mut out)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_csv_row(&self, strategy: StrategyKind, mut out: impl Write) -> Result<()> {
+        writeln!(
+            out,
+            "{strategy},{},{},{},{},{}",
+            self.secret,
+            self.rounds,
+            self.failed,
+            self.guesses.iter().join(" "),
+            self.patterns
+                .iter()
+                .map(|pattern| pattern.letters())
+                .join(" "),
+        )?;
+        Ok(())
+    }
+}
+
+/// The results of a strategy for every answer.
+///
+/// The statistics of the number of rounds are weighted by the weights of the
+/// secrets, as common words are more likely to be the secret. The histogram counts words.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub strategy: StrategyKind,
+    pub max_guesses: usize,
+    pub min: usize,
+    pub max: usize,
+    pub avg: f64,
+    /// Half the width of the 95% confidence interval of `avg`, for when the
+    /// games are a sample of the answers. It is 0 if every answer was played.
+    pub avg_margin: f64,
+    pub std_dev: f64,
+    pub percentiles: Percentiles,
+    /// The number of words found in 1, 2, ... rounds.
+    pub histogram: Vec<usize>,
+    pub failed: usize,
+    pub games: Vec<GameReport>,
+}
+
+/// The number of rounds needed to find the given percentage of the secrets.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Percentiles {
+    pub p50: usize,
+    pub p90: usize,
+    pub p95: usize,
+    pub p99: usize,
+}
+
+impl BenchReport {
+    /// How many failed games the text report lists.
+    const LISTED_FAILURES: usize = 20;
+
+    /// Sums up `games`, which are a sample of `population` answers, with the
+    /// secrets weighted by `weights`.
+    pub fn new(
+        strategy: StrategyKind,
+        games: Vec<GameReport>,
+        max_guesses: usize,
+        population: usize,
+        weights: &Weights,
+    ) -> Self {
+        let (min, max) = games
+            .iter()
+            .map(|game| game.rounds)
+            .minmax()
+            .into_option()
+            .unwrap_or_default();
+        let weighted = games
+            .iter()
+            .map(|game| (game.rounds, f64::from(weights.get(game.secret))))
+            .sorted_by_key(|&(rounds, _)| rounds)
+            .collect_vec();
+        let total_weight = weighted.iter().map(|&(_, weight)| weight).sum::<f64>();
+        let avg = weighted
+            .iter()
+            .map(|&(rounds, weight)| weight * rounds as f64)
+            .sum::<f64>()
+            / total_weight;
+        let variance = weighted
+            .iter()
+            .map(|&(rounds, weight)| weight * (rounds as f64 - avg).powi(2))
+            .sum::<f64>()
+            / total_weight;
+        // The number of games that would be as informative without weights.
+        let effective_games = total_weight.powi(2)
+            / weighted
+                .iter()
+                .map(|&(_, weight)| weight.powi(2))
+                .sum::<f64>();
+        // A single game tells nothing about the spread.
+        let sample_variance = if games.len() > 1 {
+            variance * effective_games / (effective_games - 1.0)
+        } else {
+            0.0
+        };
+        // The finite population correction: the fewer answers were left out,
+        // the closer the average is to the average of all answers.
+        let correction = population.saturating_sub(games.len()) as f64
+            / population.saturating_sub(1).max(1) as f64;
+        let percentile = |p: f64| {
+            let mut cumulative = 0.0;
+            weighted
+                .iter()
+                .find(|&&(_, weight)| {
+                    cumulative += weight;
+                    cumulative >= p * total_weight
+                })
+                .map_or(0, |&(rounds, _)| rounds)
+        };
+        let mut histogram = vec![0; max];
+        for game in &games {
+            histogram[game.rounds - 1] += 1;
+        }
+        Self {
+            strategy,
+            max_guesses,
+            min,
+            max,
+            avg,
+            avg_margin: 1.96 * (sample_variance / effective_games * correction).sqrt(),
+            std_dev: variance.sqrt(),
+            percentiles: Percentiles {
+                p50: percentile(0.5),
+                p90: percentile(0.9),
+                p95: percentile(0.95),
+                p99: percentile(0.99),
+            },
+            histogram,
+            failed: games.iter().filter(|game| game.failed).count(),
+            games,
+        }
+    }
+
+    /// Writes the reports of several strategies in one document.
+    pub fn write_all(reports: &[BenchReport], format: Format, mut out: impl Write) -> Result<()> {
+        match format {
+            Format::Text => {
+                for report in reports {
+                    report.write_text(&mut out)?;
+                }
+            }
+            Format::Json => writeln!(out, "{}", serde_json::to_string_pretty(reports)?)?,
+            Format::Csv => {
+                writeln!(out, "{CSV_SUMMARY_HEADER}")?;
+                for report in reports {
+                    report.write_csv_summary(&mut out)?;
+                }
+                writeln!(out)?;
+                writeln!(out, "{CSV_HEADER}")?;
+                for report in reports {
+                    for game in &report.games {
+                        game.write_csv_row(report.strategy, &mut out)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_csv_summary(&self, mut out: impl Write) -> Result<()> {
+        let Percentiles { p50, p90, p95, p99 } = self.percentiles;
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{p50},{p90},{p95},{p99},{}",
+            self.strategy,
+            self.games.len(),
+            self.min,
+            self.max,
+            self.avg,
+            self.avg_margin,
+            self.std_dev,
+            self.failed,
+        )?;
+        Ok(())
+    }
+
+    fn write_text(&self, mut out: impl Write) -> Result<()> {
+        writeln!(out, "Strategy: {}", self.strategy)?;
+        let games = self.games.iter().minmax_by_key(|game| game.rounds);
+        if let Some((min, max)) = games.into_option() {
+            writeln!(out, "Min: {} in {} rounds", min.secret, min.rounds)?;
+            writeln!(out, "Max: {} in {} rounds", max.secret, max.rounds)?;
+        }
+        writeln!(
+            out,
+            "Avg: {:.2} ± {:.2} (95% confidence)",
+            self.avg, self.avg_margin
+        )?;
+        writeln!(out, "Std dev: {:.2}", self.std_dev)?;
+        let Percentiles { p50, p90, p95, p99 } = self.percentiles;
+        writeln!(
+            out,
+            "Percentiles: p50 {p50}, p90 {p90}, p95 {p95}, p99 {p99}"
+        )?;
+        self.write_histogram(&mut out)?;
+        let perc = self.failed as f64 / self.games.len() as f64 * 100.0;
+        writeln!(out, "Failed words: {} ({perc:.2}%)", self.failed)?;
+        let failures = self.games.iter().filter(|game| game.failed);
+        for game in failures.clone().take(Self::LISTED_FAILURES) {
+            writeln!(out, "  {}: {}", game.secret, game.guesses.iter().join(" "))?;
+        }
+        if self.failed > Self::LISTED_FAILURES {
+            let more = self.failed - Self::LISTED_FAILURES;
+            writeln!(out, "  ... and {more} more, see --format json")?;
+        }
+        Ok(())
+    }
+
+    /// Draws a bar for the words found in each number of rounds, with all
+    /// failed games in one bar.
+    fn write_histogram(&self, mut out: impl Write) -> Result<()> {
+        const WIDTH: usize = 40;
+        let solved = (1..=self.max_guesses)
+            .map(|rounds| {
+                let count = self.histogram.get(rounds - 1).copied().unwrap_or_default();
+                (rounds.to_string(), count)
+            })
+            .collect_vec();
+        let failed = (format!(">{}", self.max_guesses), self.failed);
+        let bars = solved.into_iter().chain([failed]).collect_vec();
+        let largest = bars
+            .iter()
+            .map(|&(_, count)| count)
+            .max()
+            .unwrap_or_default();
+        for (label, count) in bars {
+            let width = (count * WIDTH).div_ceil(largest.max(1));
+            writeln!(out, "{label:>3} {:<WIDTH$} {count}", "█".repeat(width))?;
+        }
+        Ok(())
+    }
+}
+
+/// The results of several strategies side by side.
+#[derive(Debug, Clone, Serialize)]
+pub struct Comparison {
+    pub reports: Vec<BenchReport>,
+    /// The words where the number of rounds differs most between strategies,
+    /// most first.
+    pub differences: Vec<Difference>,
+}
+
+/// The number of rounds each strategy needed for a secret.
+#[derive(Debug, Clone, Serialize)]
+pub struct Difference {
+    pub secret: Word,
+    /// By strategy, in the order of [`Comparison::reports`].
+    pub rounds: Vec<usize>,
+}
+
+impl Difference {
+    fn spread(&self) -> usize {
+        let (min, max) = self
+            .rounds
+            .iter()
+            .minmax()
+            .into_option()
+            .unwrap_or((&0, &0));
+        max - min
+    }
+}
+
+impl Comparison {
+    /// Compares `reports` of the same answers, keeping the `count` words that
+    /// differ most.
+    pub fn new(reports: Vec<BenchReport>, count: usize) -> Self {
+        let differences = Self::rounds(&reports)
+            .filter(|difference| difference.spread() > 0)
+            .k_largest_by_key(count, |difference| {
+                (difference.spread(), Reverse(difference.secret))
+            })
+            .collect();
+        Self {
+            reports,
+            differences,
+        }
+    }
+
+    /// The rounds of every strategy for every secret.
+    fn rounds(reports: &[BenchReport]) -> impl Iterator<Item = Difference> + '_ {
+        let games = reports.first().map_or(0, |report| report.games.len());
+        (0..games).map(|i| Difference {
+            secret: reports[0].games[i].secret,
+            rounds: reports
+                .iter()
+                .map(|report| report.games[i].rounds)
+                .collect(),
+        })
+    }
+
+    pub fn write(&self, format: Format, mut out: impl Write) -> Result<()> {
+        match format {
+            Format::Text => self.write_text(out),
+            Format::Json => {
+                writeln!(out, "{}", serde_json::to_string_pretty(self)?)?;
+                Ok(())
+            }
+            Format::Csv => {
+                let strategies = self.reports.iter().map(|report| report.strategy).join(",");
+                writeln!(out, "secret,{strategies}")?;
+                for difference in Self::rounds(&self.reports) {
+                    writeln!(
+                        out,
+                        "{},{}",
+                        difference.secret,
+                        difference.rounds.iter().join(",")
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn write_text(&self, mut out: impl Write) -> Result<()> {
+        let width = self
+            .reports
+            .iter()
+            .map(|report| report.strategy.name().len())
+            .max()
+            .unwrap_or_default()
+            .max(6);
+        writeln!(
+            out,
+            "{:<width$} {:>5} {:>6} {:>7} {:>3} {:>3} {:>7}",
+            "", "Avg", "95% ±", "Std dev", "p50", "p90", "Failed"
+        )?;
+        for report in &self.reports {
+            let failed = report.failed as f64 / report.games.len() as f64 * 100.0;
+            writeln!(
+                out,
+                "{:<width$} {:>5.2} {:>6.2} {:>7.2} {:>3} {:>3} {:>6.2}%",
+                report.strategy.name(),
+                report.avg,
+                report.avg_margin,
+                report.std_dev,
+                report.percentiles.p50,
+                report.percentiles.p90,
+                failed,
+            )?;
+        }
+        if self.differences.is_empty() {
+            writeln!(
+                out,
+                "The strategies need the same number of rounds for every word."
+            )?;
+            return Ok(());
+        }
+        writeln!(out)?;
+        writeln!(out, "Largest differences in rounds:")?;
+        let header = self
+            .reports
+            .iter()
+            .map(|report| format!("{:>width$}", report.strategy.name()))
+            .join(" ");
+        writeln!(out, "{:<5} {header}", "")?;
+        for Difference { secret, rounds } in &self.differences {
+            let rounds = rounds
+                .iter()
+                .map(|rounds| format!("{rounds:>width$}"))
+                .join(" ");
+            writeln!(out, "{secret} {rounds}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write() {
+        let word = |w: &str| Word::try_from(w).unwrap();
+        let report = GameReport {
+            secret: word("speed"),
+            guesses: vec![word("crane"), word("speed")],
+            patterns: vec![Word::score(word("crane"), word("speed")), Pattern::SOLVED],
+            rounds: 2,
+            failed: false,
+        };
+        let write = |format| {
+            let mut out = Vec::new();
+            report
+                .write(StrategyKind::Entropy, format, &mut out)
+                .unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(
+            write(Format::Csv),
+            "strategy,secret,rounds,failed,guesses,patterns\n\
+             entropy,speed,2,false,crane speed,bbbby ggggg\n"
+        );
+        let json: serde_json::Value = serde_json::from_str(&write(Format::Json)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "secret": "speed",
+                "guesses": ["crane", "speed"],
+                "patterns": ["bbbby", "ggggg"],
+                "rounds": 2,
+                "failed": false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_bench_report() {
+        let games = ["abbey", "crane", "speed", "geese"]
+            .into_iter()
+            .zip([1, 3, 3, 9])
+            .map(|(secret, rounds)| GameReport {
+                secret: Word::try_from(secret).unwrap(),
+                guesses: Vec::new(),
+                patterns: Vec::new(),
+                rounds,
+                failed: rounds > 6,
+            })
+            .collect_vec();
+        let unweighted = Weights::default();
+        let bench = |population, weights: &Weights| {
+            BenchReport::new(
+                StrategyKind::default(),
+                games.clone(),
+                6,
+                population,
+                weights,
+            )
+        };
+        let report = bench(13, &unweighted);
+        assert_eq!((report.min, report.max, report.failed), (1, 9, 1));
+        assert_eq!(report.avg, 4.0);
+        assert_eq!(report.std_dev, 3.0);
+        // The sample variance is 12, and 4 of 13 answers were played.
+        assert_eq!(
+            report.avg_margin,
+            1.96 * (12.0 / 4.0 * 9.0 / 12.0_f64).sqrt()
+        );
+        // With every answer played, the average is exact.
+        assert_eq!(bench(4, &unweighted).avg_margin, 0.0);
+        assert_eq!(report.histogram, [1, 0, 2, 0, 0, 0, 0, 0, 1]);
+        let Percentiles { p50, p90, p95, p99 } = report.percentiles;
+        assert_eq!([p50, p90, p95, p99], [3, 9, 9, 9]);
+
+        // "abbey" counts three times, the histogram still counts words.
+        let weighted = bench(13, &[(games[0].secret, 3)].into_iter().collect());
+        assert_eq!(weighted.avg, 3.0);
+        assert_eq!(weighted.std_dev, 8.0_f64.sqrt());
+        assert_eq!(weighted.histogram, report.histogram);
+        let Percentiles { p50, p90, p95, p99 } = weighted.percentiles;
+        assert_eq!([p50, p90, p95, p99], [1, 9, 9, 9]);
+
+        let mut out = Vec::new();
+        BenchReport::write_all(&[report], Format::Csv, &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let (summary, rows) = csv.split_once("\n\n").unwrap();
+        assert_eq!(
+            summary,
+            "strategy,games,min,max,avg,avg_margin,std_dev,p50,p90,p95,p99,failed\n\
+             frequency,4,1,9,4,2.94,3,3,9,9,9,1"
+        );
+        assert_eq!(rows.lines().count(), 5);
+    }
+
+    #[test]
+    fn test_comparison() {
+        let report = |strategy, rounds: [usize; 4]| {
+            let games = ["abbey", "crane", "speed", "geese"]
+                .into_iter()
+                .zip(rounds)
+                .map(|(secret, rounds)| GameReport {
+                    secret: Word::try_from(secret).unwrap(),
+                    guesses: Vec::new(),
+                    patterns: Vec::new(),
+                    rounds,
+                    failed: false,
+                })
+                .collect();
+            BenchReport::new(strategy, games, 6, 4, &Weights::default())
+        };
+        let reports = vec![
+            report(StrategyKind::LetterFrequency, [2, 3, 4, 5]),
+            report(StrategyKind::Entropy, [2, 4, 2, 4]),
+        ];
+        let comparison = Comparison::new(reports, 2);
+        let differences = comparison
+            .differences
+            .iter()
+            .map(|difference| (difference.secret.to_string(), difference.rounds.clone()))
+            .collect_vec();
+        assert_eq!(
+            differences,
+            [
+                ("speed".to_string(), vec![4, 2]),
+                ("crane".to_string(), vec![3, 4])
+            ]
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use crate::{
+    strategy::{LetterFrequency, Strategy},
+    words::{Filter, Pattern, Weights, Word},
+};
+use std::{
+    collections::HashMap,
+    sync::{PoisonError, RwLock},
+};
+
+/// The best guess for a set of candidates and the total number of guesses
+/// needed to find every candidate when playing the best tree from there on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Solution {
+    pub guess: Word,
+    pub total_guesses: u32,
+}
+
+/// Computes decision trees that only guess candidates, and of those the ones
+/// that minimize the expected number of guesses (assuming every candidate is
+/// equally likely) and never need more than `max_depth` guesses.
+///
+/// Candidates are always allowed in hard mode, but so are the other words that
+/// are consistent with the feedback. Leaving those out keeps the search
+/// feasible, so the trees are not necessarily optimal for the game.
+///
+/// With only candidates as guesses, a sub-problem is fully described by its
+/// set of candidates and the number of guesses left. Solutions of sub-problems
+/// are memoized on that key. The memo is only locked to look up or store a
+/// solution, so several games can search at the same time.
+#[derive(Debug)]
+pub struct Solver {
+    max_depth: u8,
+    memo: RwLock<Memo>,
+}
+
+/// The solutions by set of candidates and number of guesses left.
+type Memo = HashMap<(Vec<Word>, u8), Option<Solution>>;
+
+impl Solver {
+    pub fn new(max_depth: u8) -> Self {
+        Self {
+            max_depth,
+            memo: RwLock::default(),
+        }
+    }
+
+    /// Returns the best next guess for `candidates` in a game in which
+    /// `rounds` guesses have been played already, or `None` if there is no way
+    /// to find every candidate within the maximum depth.
+    ///
+    /// This is an exhaustive search: it is only feasible for small candidate
+    /// sets or small word lists.
+    pub fn solve(&self, candidates: &[Word], rounds: usize) -> Option<Solution> {
+        let depth = usize::from(self.max_depth).saturating_sub(rounds);
+        self.solve_with_depth(candidates, depth as u8)
+    }
+
+    fn solve_with_depth(&self, candidates: &[Word], depth: u8) -> Option<Solution> {
+        match (candidates, depth) {
+            (_, 0) | ([], _) => return None,
+            (&[guess], _) => {
+                return Some(Solution {
+                    guess,
+                    total_guesses: 1,
+                })
+            }
+            (_, 1) => return None,
+            _ => {}
+        }
+        let key = (candidates.to_vec(), depth);
+        let memo = self.memo.read().unwrap_or_else(PoisonError::into_inner);
+        if let Some(&solution) = memo.get(&key) {
+            return solution;
+        }
+        drop(memo);
+
+        // Evaluate the most promising guesses first, so the bound prunes more.
+        // Of equally good guesses, the first one alphabetically wins.
+        let mut guesses = candidates
+            .iter()
+            .map(|&guess| {
+                let buckets = partition(guess, candidates);
+                (lower_bound(candidates, &buckets), guess, buckets)
+            })
+            .collect::<Vec<_>>();
+        guesses.sort_by_key(|&(lower_bound, guess, _)| (lower_bound, guess));
+
+        let mut best: Option<Solution> = None;
+        for (lower_bound, guess, buckets) in guesses {
+            if best.is_some_and(|best| lower_bound >= best.total_guesses) {
+                break;
+            }
+            // Every candidate costs this guess, the buckets cost the rest.
+            let mut total_guesses = candidates.len() as u32;
+            for bucket in &buckets {
+                match self.solve_with_depth(bucket, depth - 1) {
+                    Some(solution) => total_guesses += solution.total_guesses,
+                    None => {
+                        total_guesses = u32::MAX;
+                        break;
+                    }
+                }
+                if best.is_some_and(|best| total_guesses >= best.total_guesses) {
+                    break;
+                }
+            }
+            if best.map_or(total_guesses != u32::MAX, |best| {
+                total_guesses < best.total_guesses
+            }) {
+                best = Some(Solution {
+                    guess,
+                    total_guesses,
+                });
+            }
+        }
+
+        self.memo
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, best);
+        best
+    }
+}
+
+/// Splits `candidates` into buckets of words that give the same feedback on
+/// `guess`, leaving out `guess` itself. Words keep their relative order.
+pub fn partition(guess: Word, candidates: &[Word]) -> Vec<Vec<Word>> {
+    let mut patterns = candidates
+        .iter()
+        .map(|&secret| (Word::score(guess, secret), secret))
+        .filter(|&(pattern, _)| pattern != Pattern::SOLVED)
+        .collect::<Vec<_>>();
+    patterns.sort_by_key(|&(pattern, _)| pattern);
+    patterns
+        .chunk_by(|a, b| a.0 == b.0)
+        .map(|bucket| bucket.iter().map(|&(_, word)| word).collect())
+        .collect()
+}
+
+/// The fewest total guesses possible after guessing a word that splits
+/// `candidates` into `buckets`: one guess for every candidate, plus at least
+/// one more guess for every word in a bucket and another one for all but one.
+pub fn lower_bound(candidates: &[Word], buckets: &[Vec<Word>]) -> u32 {
+    candidates.len() as u32
+        + buckets
+            .iter()
+            .map(|bucket| 2 * bucket.len() as u32 - 1)
+            .sum::<u32>()
+}
+
+/// Plays the decision tree computed by a [`Solver`] once few candidates are
+/// left, solving (and memoizing) sub-problems as the game reaches them. With
+/// more candidates the search would take far too long, so it plays
+/// [`LetterFrequency`] until then.
+///
+/// The decision tree has to find the secret within the guesses that are left,
+/// and it only ever guesses candidates, so it is valid in normal mode too. The
+/// remaining suggestions, and the suggestions for positions that cannot be
+/// solved within the guesses left, come from [`LetterFrequency`]. This only
+/// improves the endgame: it is not an optimal strategy, and it can still fail.
+#[derive(Debug)]
+pub struct Endgame {
+    solver: Solver,
+}
+
+impl Endgame {
+    /// The most candidates to search the decision tree for.
+    pub const MAX_CANDIDATES: usize = 100;
+
+    /// The strategy for games that allow `max_guesses` guesses.
+    pub fn new(max_guesses: usize) -> Self {
+        let max_depth = max_guesses.try_into().unwrap_or(u8::MAX);
+        Self {
+            solver: Solver::new(max_depth),
+        }
+    }
+}
+
+impl Strategy for Endgame {
+    fn rank(
+        &self,
+        filter: &Filter,
+        rounds: usize,
+        candidates: &[Word],
+        guesses: &[Word],
+        weights: &Weights,
+        n: usize,
+    ) -> Vec<Word> {
+        let mut ranking = LetterFrequency.rank(filter, rounds, candidates, guesses, weights, n);
+        if candidates.len() > Self::MAX_CANDIDATES {
+            return ranking;
+        }
+        if let Some(Solution { guess, .. }) = self.solver.solve(candidates, rounds) {
+            ranking.retain(|&word| word != guess);
+            ranking.insert(0, guess);
+            ranking.truncate(n);
+        }
+        ranking
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(list: &[&str]) -> Vec<Word> {
+        list.iter().map(|&w| w.try_into().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_solve() {
+        let solver = Solver::new(6);
+
+        // Any of these tells the other two apart.
+        let candidates = words(&["crane", "crate", "trace"]);
+        assert_eq!(solver.solve(&candidates, 0).unwrap().total_guesses, 5);
+
+        // Each guess only tells whether it was right.
+        let candidates = words(&["bills", "fills", "gills", "hills"]);
+        assert_eq!(solver.solve(&candidates, 0).unwrap().total_guesses, 10);
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let candidates = words(&["bills", "fills", "gills", "hills"]);
+        assert_eq!(Solver::new(3).solve(&candidates, 0), None);
+        // After a guess, there are only three guesses left.
+        assert_eq!(Solver::new(4).solve(&candidates, 1), None);
+        assert_eq!(
+            Solver::new(4).solve(&candidates, 0).unwrap().total_guesses,
+            10
+        );
+    }
+}
@@ -1,44 +1,146 @@
-use crate::words::{LetterMap, LetterSet, Word};
+use crate::words::{LetterMap, LetterSet, Weights, Word};
+use itertools::Itertools;
 
+/// Letter statistics over a list of words, with every word counted as often as
+/// its weight: for every pair of letters, the number of words that
+/// contain both, and for every position, the number of words with each letter
+/// at that position.
 #[derive(Default, Debug, Clone)]
 pub struct LetterStats {
-    total: u32,
-    counts: LetterMap<LetterMap<u32>>,
+    total: u64,
+    counts: LetterMap<LetterMap<u64>>,
+    positions: [LetterMap<u64>; 5],
 }
 
 impl LetterStats {
-    pub fn remove_word(&mut self, word: Word) {
-        let letters = LetterSet::from(word);
-        self.total -= 1;
-        for letter in letters {
-            for other_letter in letters {
-                self.counts[letter][other_letter] -= 1;
+    pub fn new(words: &[Word], weights: &Weights) -> Self {
+        let mut total = 0;
+        let mut counts: LetterMap<LetterMap<u64>> = Default::default();
+        let mut positions: [LetterMap<u64>; 5] = Default::default();
+        for &word in words {
+            let weight = u64::from(weights.get(word));
+            total += weight;
+            for (letter, positions) in word.iter().zip(&mut positions) {
+                positions[letter] += weight;
+            }
+            let letters = LetterSet::from(word);
+            for letter in letters {
+                for other_letter in letters {
+                    counts[letter][other_letter] += weight;
+                }
             }
         }
+        Self {
+            total,
+            counts,
+            positions,
+        }
     }
 
-    pub fn relevance(&self, word: Word) -> u32 {
-        let Self { total, counts } = self;
+    /// Sums, for every distinct letter in `word`, the number of words that
+    /// guessing it would rule out in the worst case. Letters that occur in all
+    /// or none of the words are worthless.
+    pub fn relevance(&self, word: Word) -> u64 {
+        let Self { total, counts, .. } = self;
         LetterSet::from(word)
             .into_iter()
-            .map(|letter| total - counts[letter][letter].abs_diff(total / 2))
+            .map(|letter| counts[letter][letter].min(total - counts[letter][letter]))
             .sum()
     }
-}
 
-impl FromIterator<Word> for LetterStats {
-    fn from_iter<T: IntoIterator<Item = Word>>(iter: T) -> Self {
-        let mut total = 0;
-        let mut counts: LetterMap<LetterMap<u32>> = Default::default();
-        for word in iter {
-            total += 1;
-            let letters = LetterSet::from(word);
-            for letter in letters {
-                for other_letter in letters {
-                    counts[letter][other_letter] += 1;
+    /// Like [`LetterStats::relevance`], but discounts every pair of letters in
+    /// `word` by how strongly their occurrences are correlated: if one letter
+    /// (almost) always comes with the other, or (almost) never does, guessing
+    /// both tells little more than guessing one of them.
+    ///
+    /// The discount is the absolute covariance of the two letters occurring,
+    /// scaled to a number of words. It is zero for independent letters.
+    pub fn decorrelated_relevance(&self, word: Word) -> i64 {
+        let Self { total, counts, .. } = self;
+        let total = *total as i64;
+        let letters = LetterSet::from(word);
+        let redundancy = letters
+            .into_iter()
+            .tuple_combinations()
+            .map(|(a, b)| {
+                let (both, a, b) = (
+                    counts[a][b] as i64,
+                    counts[a][a] as i64,
+                    counts[b][b] as i64,
+                );
+                (both * total - a * b).abs() / total
+            })
+            .sum::<i64>();
+        self.relevance(word) as i64 - redundancy
+    }
+
+    /// Like [`LetterStats::relevance`], but also takes into account where the
+    /// letters are: a letter splits the words into those with the letter at
+    /// that position (green), elsewhere (yellow) and nowhere (gray), so this
+    /// sums the number of words ruled out in the worst case of those three.
+    /// Repeated letters only tell whether they are green.
+    pub fn positional_relevance(&self, word: Word) -> u64 {
+        let Self {
+            total,
+            counts,
+            positions,
+        } = self;
+        let mut seen = LetterSet::EMPTY;
+        word.iter()
+            .zip(positions)
+            .map(|(letter, positions)| {
+                let (green, present) = (positions[letter], counts[letter][letter]);
+                if seen.insert(letter) {
+                    total - green.max(present - green).max(total - present)
+                } else {
+                    green.min(total - green)
                 }
-            }
-        }
-        Self { total, counts }
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(list: &[&str]) -> Vec<Word> {
+        list.iter().map(|&w| Word::try_from(w).unwrap()).collect()
+    }
+
+    fn stats(list: &[&str]) -> LetterStats {
+        LetterStats::new(&words(list), &Weights::default())
+    }
+
+    #[test]
+    fn test_decorrelated_relevance() {
+        // "a" and "b" always occur together, "c" is independent of both.
+        let stats = stats(&["abcxx", "abxxx", "cxxxx", "xxxxx"]);
+        let abxxx = "abxxx".try_into().unwrap();
+        let acxxx = "acxxx".try_into().unwrap();
+        assert_eq!(stats.relevance(abxxx), stats.relevance(acxxx));
+        assert_eq!(stats.decorrelated_relevance(acxxx), 4);
+        assert_eq!(stats.decorrelated_relevance(abxxx), 3);
+    }
+
+    #[test]
+    fn test_positional_relevance() {
+        let stats = stats(&["sxxxx", "xsxxx", "xxxxs", "xxxxx"]);
+        // "s" is present in three words, but only at the front in one of them.
+        assert_eq!(stats.relevance("szzzz".try_into().unwrap()), 1);
+        assert_eq!(stats.positional_relevance("szzzz".try_into().unwrap()), 2);
+        // The repeated "s" tells "xsxxx" apart too.
+        assert_eq!(stats.positional_relevance("sszzz".try_into().unwrap()), 3);
+    }
+
+    #[test]
+    fn test_weighted_relevance() {
+        let words = words(&["axxxx", "bxxxx", "cxxxx", "xxxxx"]);
+        let azzzz = "azzzz".try_into().unwrap();
+        let weights = Weights::default();
+        assert_eq!(LetterStats::new(&words, &weights).relevance(azzzz), 1);
+        // "a" rules out the heavy "axxxx", or everything else.
+        let weights = [(words[0], 5)].into_iter().collect();
+        assert_eq!(LetterStats::new(&words, &weights).relevance(azzzz), 3);
     }
 }
@@ -0,0 +1,592 @@
+use crate::{
+    solver::{self, Endgame},
+    stats::LetterStats,
+    table::PatternTable,
+    words::{Filter, Pattern, Weights, Word},
+};
+use itertools::Itertools;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::{Serialize, Serializer};
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    fmt::{self, Debug, Display},
+    sync::Arc,
+};
+
+/// A heuristic that decides which word to guess next.
+pub trait Strategy: Debug + Send + Sync {
+    /// Returns at most `n` words out of `guesses` for the current game state,
+    /// best first. The `candidates` are the words that could still be the
+    /// secret after `rounds` guesses, and `weights` tells how likely each one
+    /// is.
+    fn rank(
+        &self,
+        filter: &Filter,
+        rounds: usize,
+        candidates: &[Word],
+        guesses: &[Word],
+        weights: &Weights,
+        n: usize,
+    ) -> Vec<Word>;
+}
+
+/// The strategies that can be selected by name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StrategyKind {
+    #[default]
+    LetterFrequency,
+    LetterPairs,
+    Positional,
+    Entropy,
+    Minimax,
+    Lookahead,
+    Endgame,
+}
+
+impl StrategyKind {
+    pub const ALL: [StrategyKind; 7] = [
+        StrategyKind::LetterFrequency,
+        StrategyKind::LetterPairs,
+        StrategyKind::Positional,
+        StrategyKind::Entropy,
+        StrategyKind::Minimax,
+        StrategyKind::Lookahead,
+        StrategyKind::Endgame,
+    ];
+
+    /// Builds the strategy for games that allow `max_guesses` guesses.
+    pub fn build(self, max_guesses: usize) -> Arc<dyn Strategy> {
+        match self {
+            StrategyKind::LetterFrequency => Arc::new(LetterFrequency),
+            StrategyKind::LetterPairs => Arc::new(LetterPairs),
+            StrategyKind::Positional => Arc::new(Positional),
+            StrategyKind::Entropy => Arc::new(Entropy),
+            StrategyKind::Minimax => Arc::new(Minimax),
+            StrategyKind::Lookahead => Arc::new(Lookahead::new(
+                Arc::new(LetterFrequency),
+                Lookahead::DEFAULT_TOP,
+            )),
+            StrategyKind::Endgame => Arc::new(Endgame::new(max_guesses)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StrategyKind::LetterFrequency => "frequency",
+            StrategyKind::LetterPairs => "pairs",
+            StrategyKind::Positional => "positional",
+            StrategyKind::Entropy => "entropy",
+            StrategyKind::Minimax => "minimax",
+            StrategyKind::Lookahead => "lookahead",
+            StrategyKind::Endgame => "endgame",
+        }
+    }
+
+    /// The next strategy in [`StrategyKind::ALL`], wrapping around.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&kind| kind == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+impl Display for StrategyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Serialize for StrategyKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// Prefers words with letters that occur in about half of the remaining
+/// candidates. Ties are broken by [`CandidateSet::tie_break`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LetterFrequency;
+
+impl Strategy for LetterFrequency {
+    fn rank(
+        &self,
+        _filter: &Filter,
+        _rounds: usize,
+        candidates: &[Word],
+        guesses: &[Word],
+        weights: &Weights,
+        n: usize,
+    ) -> Vec<Word> {
+        let stats = LetterStats::new(candidates, weights);
+        let candidates = CandidateSet::new(candidates, guesses, weights);
+        guesses
+            .iter()
+            .copied()
+            .k_largest_by_key(n, |&w| (stats.relevance(w), candidates.tie_break(w)))
+            .collect()
+    }
+}
+
+/// Like [`LetterFrequency`], but avoids words whose letters tend to occur
+/// together (or apart), as their feedback would be largely redundant.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LetterPairs;
+
+impl Strategy for LetterPairs {
+    fn rank(
+        &self,
+        _filter: &Filter,
+        _rounds: usize,
+        candidates: &[Word],
+        guesses: &[Word],
+        weights: &Weights,
+        n: usize,
+    ) -> Vec<Word> {
+        let stats = LetterStats::new(candidates, weights);
+        let candidates = CandidateSet::new(candidates, guesses, weights);
+        guesses
+            .iter()
+            .copied()
+            .k_largest_by_key(n, |&w| {
+                (stats.decorrelated_relevance(w), candidates.tie_break(w))
+            })
+            .collect()
+    }
+}
+
+/// Like [`LetterFrequency`], but also values letters by how well their
+/// position splits the remaining candidates, i.e. by their chance to be green.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Positional;
+
+impl Strategy for Positional {
+    fn rank(
+        &self,
+        _filter: &Filter,
+        _rounds: usize,
+        candidates: &[Word],
+        guesses: &[Word],
+        weights: &Weights,
+        n: usize,
+    ) -> Vec<Word> {
+        let stats = LetterStats::new(candidates, weights);
+        let candidates = CandidateSet::new(candidates, guesses, weights);
+        guesses
+            .iter()
+            .copied()
+            .k_largest_by_key(n, |&w| {
+                (stats.positional_relevance(w), candidates.tie_break(w))
+            })
+            .collect()
+    }
+}
+
+/// Prefers the guess whose feedback carries the most information about the
+/// secret, i.e. the guess with the highest Shannon entropy over the buckets of
+/// candidates that share the same feedback, with the candidates weighted. Ties are broken by [`CandidateSet::tie_break`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Entropy;
+
+impl Strategy for Entropy {
+    fn rank(
+        &self,
+        _filter: &Filter,
+        _rounds: usize,
+        candidates: &[Word],
+        guesses: &[Word],
+        weights: &Weights,
+        n: usize,
+    ) -> Vec<Word> {
+        let scorer = Scorer::new(candidates);
+        let sizes = candidates.iter().map(|&w| weights.get(w)).collect_vec();
+        let scores = guesses
+            .par_iter()
+            .map(|&guess| (guess, Self::entropy(&scorer, guess, &sizes)))
+            .collect::<Vec<_>>();
+        let candidates = CandidateSet::new(candidates, guesses, weights);
+        scores
+            .into_iter()
+            .k_largest_by(n, |a, b| {
+                a.1.total_cmp(&b.1)
+                    .then_with(|| candidates.tie_break(a.0).cmp(&candidates.tie_break(b.0)))
+            })
+            .map(|(word, _)| word)
+            .collect()
+    }
+}
+
+impl Entropy {
+    /// The entropy in bits of the feedback on `guess`, with the candidates of
+    /// `scorer` weighted by `weights`.
+    fn entropy(scorer: &Scorer<'_>, guess: Word, weights: &[u32]) -> f64 {
+        let sizes = scorer.bucket_sizes(guess, weights);
+        let total = sizes.iter().sum::<u64>() as f64;
+        sizes
+            .into_iter()
+            .filter(|&size| size > 0)
+            .map(|size| {
+                let p = size as f64 / total;
+                -p * p.log2()
+            })
+            .sum()
+    }
+}
+
+/// Prefers the guess that minimizes the number of candidates left in the worst
+/// case, i.e. the size of its largest feedback bucket. Ties are broken in favor
+/// of guesses that split the candidates into more buckets and then by
+/// [`CandidateSet::tie_break`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Minimax;
+
+impl Strategy for Minimax {
+    fn rank(
+        &self,
+        _filter: &Filter,
+        _rounds: usize,
+        candidates: &[Word],
+        guesses: &[Word],
+        weights: &Weights,
+        n: usize,
+    ) -> Vec<Word> {
+        let candidate_set = CandidateSet::new(candidates, guesses, weights);
+        let scorer = Scorer::new(candidates);
+        let ones = vec![1; candidates.len()];
+        let scores = guesses
+            .par_iter()
+            .map(|&guess| {
+                let sizes = scorer.bucket_sizes(guess, &ones);
+                let largest = sizes.iter().copied().max().unwrap_or_default();
+                let buckets = sizes.iter().filter(|&&size| size > 0).count();
+                let tie_break = candidate_set.tie_break(guess);
+                (guess, (largest, Reverse(buckets), Reverse(tie_break)))
+            })
+            .collect::<Vec<_>>();
+        scores
+            .into_iter()
+            .k_smallest_by_key(n, |&(_, score)| score)
+            .map(|(word, _)| word)
+            .collect()
+    }
+}
+
+/// Evaluates the best guesses of another strategy by playing one more guess of
+/// that strategy in every feedback bucket, and prefers the guess with the
+/// fewest expected guesses in total, with every candidate counted as often as
+/// its weight.
+///
+/// Rather than playing a cloned [`Game`](crate::game::Game) per bucket, this
+/// works on the candidates in the bucket, which is much cheaper. The follow-up
+/// guess is chosen among the guesses that are consistent with the feedback of
+/// the bucket, which are the guesses a hard mode game allows next. Normal mode
+/// would allow more. Guesses after that are estimated by a lower bound like
+/// [`solver::lower_bound`].
+#[derive(Debug)]
+pub struct Lookahead {
+    base: Arc<dyn Strategy>,
+    top: usize,
+}
+
+impl Lookahead {
+    pub const DEFAULT_TOP: usize = 10;
+
+    /// Evaluates the `top` best guesses of `base`.
+    pub fn new(base: Arc<dyn Strategy>, top: usize) -> Self {
+        Self { base, top }
+    }
+
+    /// The weighted total number of guesses needed to find every candidate
+    /// after guessing `guess`, playing `base` for the next guess out of
+    /// `guesses`.
+    fn total_guesses(
+        &self,
+        filter: &Filter,
+        rounds: usize,
+        candidates: &[Word],
+        guesses: &[Word],
+        weights: &Weights,
+        guess: Word,
+    ) -> u64 {
+        let weight = |words: &[Word]| {
+            words
+                .iter()
+                .map(|&w| u64::from(weights.get(w)))
+                .sum::<u64>()
+        };
+        let buckets = solver::partition(guess, candidates);
+        weight(candidates)
+            + buckets
+                .par_iter()
+                .map(|bucket| {
+                    let mut filter = filter.clone();
+                    filter.restrict(guess, Word::score(guess, bucket[0]));
+                    let guesses = guesses
+                        .iter()
+                        .copied()
+                        .filter(|w| w.matches(&filter))
+                        .collect_vec();
+                    let next = self
+                        .base
+                        .rank(&filter, rounds + 1, bucket, &guesses, weights, 1);
+                    let next = next.first().copied().unwrap_or(bucket[0]);
+                    // Every word in a bucket after that needs at least one
+                    // more guess, and all but the likeliest one another one.
+                    let rest = solver::partition(next, bucket)
+                        .iter()
+                        .map(|words| {
+                            let likeliest = words.iter().map(|&w| weights.get(w)).max();
+                            2 * weight(words) - u64::from(likeliest.unwrap_or_default())
+                        })
+                        .sum::<u64>();
+                    weight(bucket) + rest
+                })
+                .sum::<u64>()
+    }
+}
+
+impl Strategy for Lookahead {
+    fn rank(
+        &self,
+        filter: &Filter,
+        rounds: usize,
+        candidates: &[Word],
+        guesses: &[Word],
+        weights: &Weights,
+        n: usize,
+    ) -> Vec<Word> {
+        let mut ranking = self.base.rank(
+            filter,
+            rounds,
+            candidates,
+            guesses,
+            weights,
+            n.max(self.top),
+        );
+        let top = self.top.min(ranking.len());
+        let totals = ranking[..top]
+            .par_iter()
+            .map(|&guess| self.total_guesses(filter, rounds, candidates, guesses, weights, guess))
+            .collect::<Vec<_>>();
+        // Stable, so ties keep the order of the base strategy.
+        let mut evaluated = ranking.drain(..top).zip(totals).collect_vec();
+        evaluated.sort_by_key(|&(_, total)| total);
+        evaluated
+            .into_iter()
+            .map(|(word, _)| word)
+            .chain(ranking)
+            .take(n)
+            .collect()
+    }
+}
+
+/// Tells which guesses could be the secret, and how likely they are.
+struct CandidateSet<'a> {
+    candidates: Option<HashSet<Word>>,
+    weights: &'a Weights,
+}
+
+impl<'a> CandidateSet<'a> {
+    fn new(candidates: &[Word], guesses: &[Word], weights: &'a Weights) -> Self {
+        // The candidates are always among the guesses, so if there are as many
+        // guesses as candidates, every guess is a candidate.
+        let candidates =
+            (candidates.len() != guesses.len()).then(|| candidates.iter().copied().collect());
+        Self {
+            candidates,
+            weights,
+        }
+    }
+
+    /// Orders guesses that score the same, greatest first: words that could be
+    /// the secret, then common words (by weight), then alphabetically. This
+    /// keeps the suggestions independent of the order of the word list.
+    fn tie_break(&self, word: Word) -> (bool, u32, Reverse<Word>) {
+        let candidate = self
+            .candidates
+            .as_ref()
+            .is_none_or(|set| set.contains(&word));
+        (candidate, self.weights.get(word), Reverse(word))
+    }
+}
+
+/// Computes the feedback of guesses against a fixed list of candidates, using
+/// the [`PatternTable`] where possible.
+struct Scorer<'a> {
+    candidates: &'a [Word],
+    table: &'static PatternTable,
+    /// The answer ids of the candidates, if they all have one.
+    ids: Option<Vec<u32>>,
+}
+
+impl<'a> Scorer<'a> {
+    fn new(candidates: &'a [Word]) -> Self {
+        let table = PatternTable::global();
+        let ids = candidates.iter().map(|&w| table.answer_id(w)).collect();
+        Self {
+            candidates,
+            table,
+            ids,
+        }
+    }
+
+    /// Sums the `weights` of the candidates that end up in each feedback
+    /// bucket after `guess`.
+    fn bucket_sizes(&self, guess: Word, weights: &[u32]) -> [u64; Pattern::COUNT] {
+        let mut sizes = [0; Pattern::COUNT];
+        if let (Some(ids), Some(row)) = (&self.ids, self.table.row(guess)) {
+            for (&id, &weight) in ids.iter().zip(weights) {
+                sizes[row[id as usize].index()] += u64::from(weight);
+            }
+        } else {
+            for (&secret, &weight) in self.candidates.iter().zip(weights) {
+                sizes[Word::score(guess, secret).index()] += u64::from(weight);
+            }
+        }
+        sizes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(list: &[&str]) -> Vec<Word> {
+        list.iter().map(|&w| w.try_into().unwrap()).collect()
+    }
+
+    /// Two pairs of candidates: "p" and "q" tell the pairs apart, while "x",
+    /// "y" and "w" tell candidates of different pairs apart.
+    fn candidates() -> Vec<Word> {
+        words(&["pqxxx", "pqyyy", "rsxxx", "rswww"])
+    }
+
+    #[test]
+    fn test_entropy() {
+        let candidates = candidates();
+        let scorer = Scorer::new(&candidates);
+        let entropy = |guess: &str, weights: &[u32]| {
+            Entropy::entropy(&scorer, guess.try_into().unwrap(), weights)
+        };
+        // Tells all candidates apart.
+        assert_eq!(entropy("pxzzz", &[1; 4]), 2.0);
+        // Tells the pairs apart, and the candidates of the second pair.
+        assert_eq!(entropy("pwzzz", &[1; 4]), 1.5);
+        // Tells the pairs apart.
+        assert_eq!(entropy("pazzz", &[1; 4]), 1.0);
+        // Only tells whether the secret is "pqyyy".
+        assert!(entropy("yzzzz", &[1; 4]) < 1.0);
+
+        let guesses = words(&["pazzz", "pwzzz", "pxzzz", "yzzzz"]);
+        let rank = |weights: &Weights| {
+            Entropy.rank(&Filter::default(), 0, &candidates, &guesses, weights, 4)
+        };
+        let ranking = rank(&Weights::default());
+        assert_eq!(ranking, words(&["pxzzz", "pwzzz", "pazzz", "yzzzz"]));
+
+        // If the first pair is much more likely, it is more important to tell
+        // its candidates apart than to tell the pairs apart.
+        assert!(entropy("yzzzz", &[4, 4, 1, 1]) > entropy("pazzz", &[4, 4, 1, 1]));
+        let weights = candidates[..2].iter().map(|&w| (w, 4)).collect();
+        assert_eq!(rank(&weights), words(&["pxzzz", "yzzzz", "pwzzz", "pazzz"]));
+    }
+
+    #[test]
+    fn test_tie_break() {
+        let candidates = words(&["axxxx", "bxxxx"]);
+        let filter = Filter::default();
+        let rank = |guesses: &[Word], weights: &Weights| {
+            LetterFrequency.rank(&filter, 0, &candidates, guesses, weights, 3)
+        };
+        // All of them tell the candidates apart.
+        let guesses = words(&["azzzz", "axxxx", "bxxxx"]);
+        let weights = Weights::default();
+        assert_eq!(
+            rank(&guesses, &weights),
+            words(&["axxxx", "bxxxx", "azzzz"])
+        );
+        assert_eq!(rank(&candidates, &weights), words(&["axxxx", "bxxxx"]));
+        // The heavier candidate wins.
+        let weights = [(candidates[1], 2)].into_iter().collect();
+        assert_eq!(
+            rank(&guesses, &weights),
+            words(&["bxxxx", "axxxx", "azzzz"])
+        );
+        assert_eq!(rank(&candidates, &weights), words(&["bxxxx", "axxxx"]));
+    }
+
+    #[test]
+    fn test_minimax() {
+        let candidates = candidates();
+        let filter = Filter::default();
+        // "pqrzz" has more letters in half of the candidates, but "p" and "q"
+        // are redundant, so it leaves two candidates in either case. "pxzzz"
+        // tells all of them apart.
+        let guesses = words(&["pqrzz", "pxzzz"]);
+        let weights = Weights::default();
+        let best =
+            |strategy: &dyn Strategy| strategy.rank(&filter, 0, &candidates, &guesses, &weights, 1);
+        assert_eq!(best(&LetterFrequency), words(&["pqrzz"]));
+        assert_eq!(best(&Minimax), words(&["pxzzz"]));
+    }
+
+    #[test]
+    fn test_minimax_bucket_count() {
+        let candidates = candidates();
+        // Both leave two candidates in the worst case, but "pwzzz" splits the
+        // candidates into three buckets instead of two.
+        let guesses = words(&["pazzz", "pwzzz"]);
+        let weights = Weights::default();
+        let ranking = Minimax.rank(&Filter::default(), 0, &candidates, &guesses, &weights, 2);
+        assert_eq!(ranking, words(&["pwzzz", "pazzz"]));
+    }
+
+    #[test]
+    fn test_lookahead() {
+        let candidates = candidates();
+        let filter = Filter::default();
+        // "pqrzz" leaves two candidates in either case, which need three more
+        // guesses, so ten in total. "pxzzz" tells all of them apart, so the
+        // next guess finds the secret: eight in total.
+        let guesses = words(&["pqrzz", "pxzzz"]);
+        let weights = Weights::default();
+        let lookahead = Lookahead::new(Arc::new(LetterFrequency), 2);
+        assert_eq!(
+            LetterFrequency.rank(&filter, 0, &candidates, &guesses, &weights, 2),
+            guesses
+        );
+        let all = [&guesses[..], &candidates].concat();
+        let total = |guess, weights: &Weights| {
+            lookahead.total_guesses(&filter, 0, &candidates, &all, weights, guess)
+        };
+        assert_eq!(total(guesses[0], &weights), 10);
+        assert_eq!(total(guesses[1], &weights), 8);
+        assert_eq!(
+            lookahead.rank(&filter, 0, &candidates, &guesses, &weights, 2),
+            words(&["pxzzz", "pqrzz"])
+        );
+        // With "pqxxx" counted three times, it is the next guess in its bucket:
+        // 6 + (4 + 1) + (2 + 1) guesses.
+        let heavy = [(candidates[0], 3)].into_iter().collect();
+        assert_eq!(total(guesses[0], &heavy), 14);
+    }
+
+    #[test]
+    fn test_lookahead_follow_up() {
+        let candidates = words(&["bxxxx", "cxxxx", "dxxxx", "exxxx"]);
+        let filter = Filter::default();
+        let lookahead = Lookahead::new(Arc::new(LetterFrequency), 1);
+        let guess = "zzzzz".try_into().unwrap();
+        // After "zzzzz", guessing a candidate finds it, but cannot tell the
+        // other three apart, so finding all four takes 4 + 4 + 5 guesses.
+        let mut guesses = [&[guess][..], &candidates].concat();
+        let weights = Weights::default();
+        let total = |guesses: &[Word]| {
+            lookahead.total_guesses(&filter, 0, &candidates, guesses, &weights, guess)
+        };
+        assert_eq!(total(&guesses), 13);
+        // "bcdxx" cannot be the secret, but hard mode allows it after
+        // "zzzzz", and it tells all candidates apart.
+        guesses.push("bcdxx".try_into().unwrap());
+        assert_eq!(total(&guesses), 12);
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{
+    bitset::BitSet,
+    words::{Pattern, Word},
+};
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
+use rayon::{
+    iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator},
+    slice::ParallelSliceMut,
+};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+/// The pattern of every allowed guess against every answer, i.e. a row of
+/// [`Word::answers`] patterns for every word in [`Word::list`].
+///
+/// For the embedded word lists that is about 35 MB, so it takes a moment to
+/// compute. It can be cached on disk, see [`set_cache_dir`].
+pub struct PatternTable {
+    answers: usize,
+    patterns: Vec<Pattern>,
+    guess_ids: HashMap<Word, u32>,
+    answer_ids: HashMap<Word, u32>,
+}
+
+static TABLE: OnceLock<PatternTable> = OnceLock::new();
+static CACHE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Caches the table for the current word lists in `dir`, instead of computing
+/// it on every run. This has to happen before the table is first used.
+pub fn set_cache_dir(dir: PathBuf) -> Result<()> {
+    CACHE_DIR
+        .set(dir)
+        .map_err(|_| eyre!("cache directory is already set"))
+}
+
+impl PatternTable {
+    const MAGIC: &'static [u8; 8] = b"WRDLPT01";
+
+    /// The table for the current word lists, computed (or loaded from the
+    /// cache) on first use.
+    pub fn global() -> &'static PatternTable {
+        TABLE.get_or_init(|| {
+            let (guesses, answers) = (Word::list(), Word::answers());
+            let Some(dir) = CACHE_DIR.get() else {
+                return Self::build(guesses, answers);
+            };
+            let path = dir.join(format!("patterns-{:016x}.bin", checksum(guesses, answers)));
+            if let Ok(table) = Self::load(&path, guesses, answers) {
+                return table;
+            }
+            let table = Self::build(guesses, answers);
+            // The cache is only an optimization, so failing to write it is fine.
+            let _unused = fs::create_dir_all(dir)
+                .and_then(|()| fs::write(&path, table.to_bytes(guesses, answers)));
+            table
+        })
+    }
+
+    pub fn build(guesses: &[Word], answers: &[Word]) -> Self {
+        let mut patterns = vec![Pattern::SOLVED; guesses.len() * answers.len()];
+        if !answers.is_empty() {
+            patterns
+                .par_chunks_mut(answers.len())
+                .zip(guesses.par_iter())
+                .for_each(|(row, &guess)| {
+                    for (pattern, &answer) in row.iter_mut().zip(answers) {
+                        *pattern = Word::score(guess, answer);
+                    }
+                });
+        }
+        Self::new(guesses, answers, patterns)
+    }
+
+    fn new(guesses: &[Word], answers: &[Word], patterns: Vec<Pattern>) -> Self {
+        Self {
+            answers: answers.len(),
+            patterns,
+            guess_ids: guesses.iter().copied().zip(0..).collect(),
+            answer_ids: answers.iter().copied().zip(0..).collect(),
+        }
+    }
+
+    /// The position of `answer` in [`Word::answers`].
+    pub fn answer_id(&self, answer: Word) -> Option<u32> {
+        self.answer_ids.get(&answer).copied()
+    }
+
+    /// The patterns of `guess` against all answers, by answer id. Guesses that
+    /// are not in the word list have no row.
+    pub fn row(&self, guess: Word) -> Option<&[Pattern]> {
+        let id = *self.guess_ids.get(&guess)? as usize;
+        Some(&self.patterns[id * self.answers..(id + 1) * self.answers])
+    }
+
+    /// The answers that give `pattern` on `guess`, by answer id. Intersecting
+    /// this with a set of answers filters them a block at a time. The sets
+    /// are built on demand, as all of them would take more than 1 GB.
+    pub fn answers_matching(&self, guess: Word, pattern: Pattern) -> Option<BitSet> {
+        let row = self.row(guess)?;
+        Some(BitSet::from_fn(self.answers, |id| row[id] == pattern))
+    }
+
+    fn to_bytes(&self, guesses: &[Word], answers: &[Word]) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(16 + self.patterns.len());
+        bytes.extend(Self::MAGIC);
+        bytes.extend(checksum(guesses, answers).to_le_bytes());
+        bytes.extend(self.patterns.iter().map(|pattern| pattern.index() as u8));
+        bytes
+    }
+
+    fn load(path: &Path, guesses: &[Word], answers: &[Word]) -> Result<Self> {
+        let bytes = fs::read(path).wrap_err_with(|| format!("cannot read {}", path.display()))?;
+        let Some((header, patterns)) = bytes.split_at_checked(16) else {
+            bail!("{} is truncated", path.display());
+        };
+        if &header[..8] != Self::MAGIC
+            || header[8..] != checksum(guesses, answers).to_le_bytes()
+            || patterns.len() != guesses.len() * answers.len()
+        {
+            bail!(
+                "{} does not belong to the current word lists",
+                path.display()
+            );
+        }
+        let patterns = patterns
+            .iter()
+            .map(|&pattern| Pattern::try_from(pattern))
+            .collect::<Result<_>>()?;
+        Ok(Self::new(guesses, answers, patterns))
+    }
+}
+
+/// A stable FNV-1a hash of the word lists, to tell whether a cached table is
+/// still valid.
+fn checksum(guesses: &[Word], answers: &[Word]) -> u64 {
+    [guesses, answers]
+        .into_iter()
+        .flat_map(|list| {
+            list.iter()
+                .flat_map(|word| word.iter().map(|letter| char::from(letter) as u8))
+                .chain([b'\n'])
+        })
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table() -> Result<()> {
+        let words = ["crane", "speed", "abbey", "geese"].map(|word| Word::try_from(word).unwrap());
+        let (guesses, answers) = (&words[..], &words[1..]);
+        let table = PatternTable::build(guesses, answers);
+        for &guess in guesses {
+            let row = table.row(guess).unwrap();
+            for &answer in answers {
+                let id = table.answer_id(answer).unwrap() as usize;
+                assert_eq!(row[id], Word::score(guess, answer));
+            }
+        }
+        let pattern = Word::score(words[0], words[1]);
+        let matching = table.answers_matching(words[0], pattern).unwrap();
+        let matching = matching.select(answers).copied().collect::<Vec<_>>();
+        assert_eq!(matching, [words[1]]);
+        assert!(table.row("zzzzz".try_into()?).is_none());
+        assert!(table.answer_id(words[0]).is_none());
+
+        let path = std::env::temp_dir().join(format!("wrdl-test-{}.bin", std::process::id()));
+        fs::write(&path, table.to_bytes(guesses, answers))?;
+        let loaded = PatternTable::load(&path, guesses, answers);
+        let outdated = PatternTable::load(&path, guesses, &words[2..]);
+        fs::remove_file(&path)?;
+        assert_eq!(loaded?.patterns, table.patterns);
+        assert!(outdated.is_err());
+        Ok(())
+    }
+}
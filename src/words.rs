@@ -1,10 +1,16 @@
 use color_eyre::{
-    eyre::{bail, eyre},
+    eyre::{bail, eyre, WrapErr},
     Report, Result,
 };
+use serde::{Serialize, Serializer};
 use std::{
+    collections::{HashMap, HashSet},
     fmt::{self, Debug, Display, Write},
+    fs::File,
+    io::{BufRead, BufReader},
     ops::{Index, IndexMut},
+    path::Path,
+    str::FromStr,
     sync::OnceLock,
 };
 
@@ -113,10 +119,6 @@ impl LetterSet {
         self.0 = new;
         old != new
     }
-
-    pub fn intersect(self, other: Self) -> Self {
-        Self(self.0 & other.0)
-    }
 }
 
 impl Debug for LetterSet {
@@ -170,7 +172,8 @@ impl Iterator for LetterSetIter {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// Words are ordered alphabetically.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
 pub struct Word([Letter; 5]);
 
 impl Display for Word {
@@ -180,16 +183,26 @@ impl Display for Word {
     }
 }
 
+impl Serialize for Word {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 impl Word {
+    /// All words that are allowed as a guess.
     pub fn list() -> &'static [Word] {
-        static LIST: OnceLock<Vec<Word>> = OnceLock::new();
+        &lists().guesses
+    }
 
-        LIST.get_or_init(|| {
-            include_str!("../words")
-                .split_whitespace()
-                .map(|w| w.try_into().expect("incorrect word in word list"))
-                .collect()
-        })
+    /// The words that can be the secret, always a subset of [`Word::list`].
+    pub fn answers() -> &'static [Word] {
+        &lists().answers
+    }
+
+    /// The weights of the words in the current word lists.
+    pub fn weights() -> &'static Weights {
+        &lists().weights
     }
 
     #[inline]
@@ -213,12 +226,35 @@ impl Word {
         self.letter_count()
             .iter()
             .zip(filter.min_count.iter())
-            .all(|(actual, minimum)| actual >= minimum)
+            .zip(filter.max_count.iter())
+            .all(|((actual, minimum), maximum)| (minimum..=maximum).contains(&actual))
     }
 
     pub fn iter(&self) -> impl Iterator<Item = Letter> + '_ {
         self.0.iter().copied()
     }
+
+    /// Scores `guess` against `secret` by the rules of Wordle: letters in the
+    /// right position are green, other letters in the secret are yellow from
+    /// left to right for as many times as they occur in the rest of the secret.
+    pub fn score(guess: Word, secret: Word) -> Pattern {
+        let mut feedback = [Feedback::Black; 5];
+        let mut missing_letters: LetterMap<u8> = Default::default();
+        for (pos, (guess, letter)) in guess.iter().zip(secret.iter()).enumerate() {
+            if guess == letter {
+                feedback[pos] = Feedback::Green;
+            } else {
+                missing_letters[letter] += 1;
+            }
+        }
+        for (feedback, letter) in feedback.iter_mut().zip(guess.iter()) {
+            if *feedback == Feedback::Black && missing_letters[letter] > 0 {
+                missing_letters[letter] -= 1;
+                *feedback = Feedback::Yellow;
+            }
+        }
+        feedback.into()
+    }
 }
 
 impl TryFrom<&str> for Word {
@@ -242,10 +278,151 @@ impl From<[Letter; 5]> for Word {
     }
 }
 
+/// How likely each word is to be the secret, relative to the other words.
+/// Words without a weight have weight 1.
+#[derive(Debug, Clone, Default)]
+pub struct Weights(HashMap<Word, u32>);
+
+impl Weights {
+    pub fn get(&self, word: Word) -> u32 {
+        self.0.get(&word).copied().unwrap_or(1)
+    }
+}
+
+impl FromIterator<(Word, u32)> for Weights {
+    fn from_iter<T: IntoIterator<Item = (Word, u32)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// A list of words, each with an optional weight.
+#[derive(Debug, Clone, Default)]
+pub struct WordList {
+    words: Vec<Word>,
+    weights: Weights,
+}
+
+impl WordList {
+    /// The word list that is embedded in the binary.
+    pub fn embedded() -> Self {
+        Self::from_reader(include_str!("../words").as_bytes()).expect("incorrect word in word list")
+    }
+
+    /// The list of answers that is embedded in the binary: common words, out
+    /// of the embedded word list.
+    pub fn embedded_answers() -> Self {
+        Self::from_reader(include_str!("../answers").as_bytes())
+            .expect("incorrect word in list of answers")
+    }
+
+    pub fn words(&self) -> &[Word] {
+        &self.words
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).wrap_err_with(|| format!("cannot open {}", path.display()))?;
+        Self::from_reader(BufReader::new(file))
+            .wrap_err_with(|| format!("invalid word list {}", path.display()))
+    }
+
+    /// Reads one word per line, optionally followed by its weight. Blank lines
+    /// are ignored.
+    pub fn from_reader(reader: impl BufRead) -> Result<Self> {
+        let mut list = Self::default();
+        let mut line_numbers = HashMap::new();
+        for (line, line_number) in reader.lines().zip(1..) {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (word, weight) =
+                parse_line(&line).wrap_err_with(|| format!("line {line_number}: \"{line}\""))?;
+            if let Some(first) = line_numbers.insert(word, line_number) {
+                bail!("line {line_number}: \"{word}\" is already listed on line {first}");
+            }
+            list.words.push(word);
+            if let Some(weight) = weight {
+                list.weights.0.insert(word, weight);
+            }
+        }
+        if list.words.is_empty() {
+            bail!("word list is empty");
+        }
+        Ok(list)
+    }
+}
+
+struct Lists {
+    guesses: Vec<Word>,
+    answers: Vec<Word>,
+    weights: Weights,
+}
+
+impl Lists {
+    fn new(guesses: WordList, answers: Option<WordList>) -> Self {
+        let WordList {
+            words: mut guesses,
+            mut weights,
+        } = guesses;
+        let Some(answers) = answers else {
+            return Self {
+                answers: guesses.clone(),
+                guesses,
+                weights,
+            };
+        };
+        // Every answer must be allowed as a guess too.
+        let known: HashSet<Word> = guesses.iter().copied().collect();
+        guesses.extend(answers.words.iter().filter(|word| !known.contains(word)));
+        weights.0.extend(answers.weights.0);
+        Self {
+            guesses,
+            answers: answers.words,
+            weights,
+        }
+    }
+}
+
+static LISTS: OnceLock<Lists> = OnceLock::new();
+
+fn lists() -> &'static Lists {
+    LISTS.get_or_init(|| Lists::new(WordList::embedded(), Some(WordList::embedded_answers())))
+}
+
+/// Replaces the embedded word list by the allowed `guesses` and, optionally, a
+/// separate list of `answers`. This has to happen before any word list is used.
+pub fn install(guesses: WordList, answers: Option<WordList>) -> Result<()> {
+    LISTS
+        .set(Lists::new(guesses, answers))
+        .map_err(|_| eyre!("word lists are already in use"))
+}
+
+/// Parses a line of a word list: a word, optionally followed by its weight.
+fn parse_line(line: &str) -> Result<(Word, Option<u32>)> {
+    let mut columns = line.split_whitespace();
+    let word = columns.next().ok_or_else(|| eyre!("missing word"))?;
+    let word = Word::try_from(word)?;
+    let weight = columns
+        .next()
+        .map(|weight| match weight.parse() {
+            Ok(weight) if weight > 0 => Ok(weight),
+            _ => Err(eyre!("weight must be a positive integer: {weight}")),
+        })
+        .transpose()?;
+    if let Some(column) = columns.next() {
+        bail!("unexpected column: {column}");
+    }
+    Ok((word, weight))
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Filter {
     pub mask: [LetterSet; 5],
     pub min_count: LetterMap<u8>,
+    /// Known once a letter is marked black while other occurrences of the same
+    /// letter in the guess are not.
+    pub max_count: LetterMap<u8>,
 }
 
 impl Default for Filter {
@@ -253,11 +430,12 @@ impl Default for Filter {
         Self {
             mask: [LetterSet::FULL; 5],
             min_count: Default::default(),
+            max_count: LetterMap([5; 26]),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum Feedback {
     #[default]
     Black,
@@ -265,10 +443,132 @@ pub enum Feedback {
     Green,
 }
 
+/// The feedback on every letter of a guess, encoded as a base 3 number with the
+/// feedback on the first letter as the most significant digit.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pattern(u8);
+
+impl Pattern {
+    /// The number of distinct patterns.
+    pub const COUNT: usize = 243;
+    pub const SOLVED: Pattern = Pattern(242);
+
+    /// A number in `0..Pattern::COUNT`, e.g. to index arrays.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn feedback(self) -> [Feedback; 5] {
+        let mut value = self.0;
+        let mut feedback = [Feedback::Black; 5];
+        for feedback in feedback.iter_mut().rev() {
+            *feedback = match value % 3 {
+                0 => Feedback::Black,
+                1 => Feedback::Yellow,
+                _ => Feedback::Green,
+            };
+            value /= 3;
+        }
+        feedback
+    }
+
+    /// A letter per position: `b` for black, `y` for yellow and `g` for green,
+    /// e.g. "bygbb", like [`Pattern::from_str`] parses.
+    pub fn letters(self) -> String {
+        self.feedback()
+            .map(|feedback| match feedback {
+                Feedback::Black => 'b',
+                Feedback::Yellow => 'y',
+                Feedback::Green => 'g',
+            })
+            .iter()
+            .collect()
+    }
+}
+
+impl From<[Feedback; 5]> for Pattern {
+    fn from(value: [Feedback; 5]) -> Self {
+        Self(
+            value
+                .into_iter()
+                .fold(0, |pattern, feedback| pattern * 3 + feedback as u8),
+        )
+    }
+}
+
+impl TryFrom<u8> for Pattern {
+    type Error = Report;
+
+    fn try_from(value: u8) -> Result<Self> {
+        if usize::from(value) >= Pattern::COUNT {
+            bail!("invalid pattern: {value}");
+        }
+        Ok(Self(value))
+    }
+}
+
+impl From<Pattern> for [Feedback; 5] {
+    fn from(value: Pattern) -> Self {
+        value.feedback()
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for feedback in self.feedback() {
+            f.write_char(match feedback {
+                Feedback::Black => '⬛',
+                Feedback::Yellow => '🟨',
+                Feedback::Green => '🟩',
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Pattern").field(&self.letters()).finish()
+    }
+}
+
+impl Serialize for Pattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.letters())
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = Report;
+
+    /// Parses a letter per position: `b` for black, `y` for yellow and `g` for
+    /// green, e.g. "bygbb". The emoji that [`Pattern`] displays as work too.
+    fn from_str(s: &str) -> Result<Self> {
+        let feedback = s
+            .chars()
+            .map(|ch| match ch.to_ascii_lowercase() {
+                'b' | '⬛' => Ok(Feedback::Black),
+                'y' | '🟨' => Ok(Feedback::Yellow),
+                'g' | '🟩' => Ok(Feedback::Green),
+                _ => Err(eyre!("invalid feedback \"{ch}\", expected b, y or g")),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let feedback: [Feedback; 5] = feedback
+            .try_into()
+            .map_err(|_| eyre!("feedback must have length 5"))?;
+        Ok(feedback.into())
+    }
+}
+
 impl Filter {
-    pub fn restrict(&mut self, word: Word, feedback: [Feedback; 5]) {
+    /// Narrows the filter down to the words that would give `pattern` on
+    /// `word`. Green and yellow letters are counted before black letters are
+    /// handled, so the outcome does not depend on the order of the letters.
+    pub fn restrict(&mut self, word: Word, pattern: Pattern) {
+        let feedback = pattern.feedback();
         let mut min_count: LetterMap<u8> = Default::default();
-        for (pos, (letter, feedback)) in word.iter().zip(feedback.iter()).enumerate() {
+        let mut black = LetterSet::EMPTY;
+        for (pos, (letter, feedback)) in word.iter().zip(feedback).enumerate() {
             match feedback {
                 Feedback::Green => {
                     self.mask[pos] = LetterSet::from([letter]);
@@ -279,32 +579,81 @@ impl Filter {
                     min_count[letter] += 1
                 }
                 Feedback::Black => {
-                    if min_count[letter] > 0 {
-                        self.mask[pos].remove(letter);
-                    } else {
-                        self.mask.iter_mut().for_each(|set| {
-                            set.remove(letter);
-                        });
-                    }
+                    black.insert(letter);
                 }
             }
         }
+        for (pos, (letter, feedback)) in word.iter().zip(feedback).enumerate() {
+            if feedback != Feedback::Black {
+                continue;
+            }
+            if min_count[letter] > 0 {
+                self.mask[pos].remove(letter);
+            } else {
+                self.mask.iter_mut().for_each(|set| {
+                    set.remove(letter);
+                });
+            }
+        }
         self.min_count
             .iter_mut()
             .zip(min_count.iter())
             .for_each(|(cur, new)| *cur = (*cur).max(*new));
+        // A black letter means the secret has no more of it than the guess has
+        // yellow or green.
+        for letter in black {
+            self.max_count[letter] = self.max_count[letter].min(min_count[letter]);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use itertools::Itertools;
+    use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+    use std::iter;
 
     #[test]
     fn test_list() {
         let list = Word::list();
 
         assert_eq!(list.len(), 14855);
+        let guesses: HashSet<Word> = list.iter().copied().collect();
+        let answers = Word::answers();
+        assert!(answers.len() < list.len());
+        assert!(answers.iter().all(|answer| guesses.contains(answer)));
+    }
+
+    #[test]
+    fn test_parse_line() {
+        let crane = Word::try_from("crane").unwrap();
+        assert_eq!(parse_line("crane").unwrap(), (crane, None));
+        assert_eq!(parse_line("crane 42").unwrap(), (crane, Some(42)));
+        assert!(parse_line("crane 0").is_err());
+        assert!(parse_line("crane 42 7").is_err());
+        assert!(parse_line("cranes").is_err());
+        assert!(parse_line("").is_err());
+    }
+
+    #[test]
+    fn test_word_list() {
+        let list = WordList::from_reader("crane 3\n\nslate\n".as_bytes()).unwrap();
+        let [crane, slate] = ["crane", "slate"].map(|w| Word::try_from(w).unwrap());
+        assert_eq!(list.words, &[crane, slate]);
+        assert_eq!(list.weights.get(crane), 3);
+        assert_eq!(list.weights.get(slate), 1);
+
+        let error = WordList::from_reader("crane\nslate\nsl8te\n".as_bytes()).unwrap_err();
+        assert!(error.to_string().starts_with("line 3"), "{error}");
+
+        let error = WordList::from_reader("crane\nslate\ncrane\n".as_bytes()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 3: \"crane\" is already listed on line 1"
+        );
+
+        assert!(WordList::from_reader("\n".as_bytes()).is_err());
     }
 
     #[test]
@@ -313,7 +662,7 @@ mod tests {
         use Feedback::*;
         guess.restrict(
             "ready".try_into().unwrap(),
-            [Yellow, Black, Yellow, Green, Black],
+            [Yellow, Black, Yellow, Green, Black].into(),
         );
 
         assert_eq!(
@@ -334,6 +683,12 @@ mod tests {
                     map[Letter::new('a')] = 1;
                     map[Letter::new('d')] = 1;
                     map
+                },
+                max_count: {
+                    let mut map = LetterMap([5; 26]);
+                    map[Letter::new('e')] = 0;
+                    map[Letter::new('y')] = 0;
+                    map
                 }
             }
         );
@@ -348,4 +703,178 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_matches_duplicate_letters() {
+        use Feedback::*;
+        let e = Letter::new('e');
+
+        // Secret "abide": the second 'e' is black, so there is exactly one.
+        let mut filter = Filter::default();
+        filter.restrict(
+            "speed".try_into().unwrap(),
+            [Black, Black, Yellow, Black, Yellow].into(),
+        );
+        assert_eq!((filter.min_count[e], filter.max_count[e]), (1, 1));
+        for word in ["abide", "drive"] {
+            let word: Word = word.try_into().unwrap();
+            assert!(word.matches(&filter), "{word}");
+        }
+        for word in ["eddie", "edged", "speed"] {
+            let word: Word = word.try_into().unwrap();
+            assert!(!word.matches(&filter), "{word}");
+        }
+
+        // Secret "crane": only the last 'e' is green.
+        let mut filter = Filter::default();
+        filter.restrict(
+            "eerie".try_into().unwrap(),
+            [Black, Black, Yellow, Black, Green].into(),
+        );
+        assert_eq!((filter.min_count[e], filter.max_count[e]), (1, 1));
+        let word: Word = "crane".try_into().unwrap();
+        assert!(word.matches(&filter));
+
+        // Secret "geese": all three 'e's are there, the count stays open.
+        let mut filter = Filter::default();
+        filter.restrict(
+            "eerie".try_into().unwrap(),
+            [Yellow, Green, Black, Black, Green].into(),
+        );
+        assert_eq!((filter.min_count[e], filter.max_count[e]), (3, 5));
+        let word: Word = "geese".try_into().unwrap();
+        assert!(word.matches(&filter));
+    }
+
+    #[test]
+    fn test_score() {
+        use Feedback::*;
+        for (guess, secret, pattern) in [
+            ("crane", "crane", [Green; 5]),
+            ("soare", "crane", [Black, Black, Green, Yellow, Green]),
+            ("eerie", "crane", [Black, Black, Yellow, Black, Green]),
+            ("speed", "abide", [Black, Black, Yellow, Black, Yellow]),
+            ("speed", "abbey", [Black, Black, Black, Green, Black]),
+            ("eerie", "geese", [Yellow, Green, Black, Black, Green]),
+            ("lills", "hills", [Black, Green, Green, Green, Green]),
+        ] {
+            let guess = Word::try_from(guess).unwrap();
+            let secret = Word::try_from(secret).unwrap();
+            assert_eq!(
+                Word::score(guess, secret),
+                pattern.into(),
+                "{guess} {secret}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pattern() {
+        use Feedback::*;
+        for index in 0..Pattern::COUNT {
+            let pattern = Pattern(index as u8);
+            assert_eq!(Pattern::from(pattern.feedback()), pattern);
+            assert_eq!(pattern.to_string().parse::<Pattern>().unwrap(), pattern);
+        }
+        assert_eq!(Pattern::from([Green; 5]), Pattern::SOLVED);
+
+        let pattern: Pattern = "bygBB".parse().unwrap();
+        assert_eq!(pattern.feedback(), [Black, Yellow, Green, Black, Black]);
+        assert_eq!(pattern.to_string(), "⬛🟨🟩⬛⬛");
+        assert_eq!(format!("{pattern:?}"), "Pattern(\"bygbb\")");
+        assert!("bygb".parse::<Pattern>().is_err());
+        assert!("bygbbb".parse::<Pattern>().is_err());
+        assert!("bygbx".parse::<Pattern>().is_err());
+    }
+
+    #[test]
+    fn test_restrict_order_independent() {
+        use Feedback::*;
+        let e = Letter::new('e');
+        let speed: Word = "speed".try_into().unwrap();
+
+        // Secret "abbey": the black 'e' comes before the green one.
+        let mut filter = Filter::default();
+        filter.restrict(speed, [Black, Black, Black, Green, Black].into());
+        assert_eq!((filter.min_count[e], filter.max_count[e]), (1, 1));
+        assert_eq!(filter.mask[3], LetterSet::from([e]));
+        let word: Word = "abbey".try_into().unwrap();
+        assert!(word.matches(&filter));
+
+        // It does not matter which of the two 'e's is marked yellow.
+        let mut first = Filter::default();
+        first.restrict(speed, [Black, Black, Yellow, Black, Black].into());
+        let mut second = Filter::default();
+        second.restrict(speed, [Black, Black, Black, Yellow, Black].into());
+        assert_eq!(first, second);
+        assert_eq!((first.min_count[e], first.max_count[e]), (1, 1));
+        assert!(first.mask[0].contains(e));
+        assert!(!first.mask[2].contains(e));
+        assert!(!first.mask[3].contains(e));
+    }
+
+    /// All words that can be made from `letters`, which covers every pattern
+    /// of duplicate letters if there are enough of them.
+    fn all_words(letters: &str) -> Vec<Word> {
+        iter::repeat_n(letters.chars(), 5)
+            .multi_cartesian_product()
+            .map(|letters| {
+                letters
+                    .into_iter()
+                    .collect::<String>()
+                    .as_str()
+                    .try_into()
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    /// Asserts that `secret` matches the filter built from its own feedback on
+    /// every guess.
+    fn assert_secret_matches(guesses: &[Word], secret: Word) {
+        for &guess in guesses {
+            let mut filter = Filter::default();
+            filter.restrict(guess, Word::score(guess, secret));
+            assert!(secret.matches(&filter), "guess {guess}, secret {secret}");
+        }
+    }
+
+    #[test]
+    fn test_restrict_exhaustive() {
+        let words = all_words("abcd");
+        for &secret in &words {
+            assert_secret_matches(&words, secret);
+        }
+    }
+
+    #[test]
+    fn test_restrict_exact() {
+        // A word matches the filter if and only if it gives the same feedback.
+        let words = all_words("abc");
+        for &guess in &words {
+            let feedback = words
+                .iter()
+                .map(|&word| Word::score(guess, word))
+                .collect_vec();
+            for &expected in feedback.iter().unique() {
+                let mut filter = Filter::default();
+                filter.restrict(guess, expected);
+                for (&word, &actual) in words.iter().zip(&feedback) {
+                    assert_eq!(
+                        word.matches(&filter),
+                        actual == expected,
+                        "guess {guess}, feedback {expected:?}, word {word}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "slow, run with --release"]
+    fn test_restrict_word_list() {
+        let list = Word::list();
+        list.par_iter()
+            .for_each(|&secret| assert_secret_matches(list, secret));
+    }
 }